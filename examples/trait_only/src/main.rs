@@ -5,7 +5,8 @@ use std::pin::Pin;
 use reqwest::{header, Client, Method, Request, Url};
 
 use psn_api_rs::{
-    models::PSNUser,
+    attachment::Attachment,
+    models::{PSNUser, TokenValidation},
     traits::{EncodeUrl, PSNRequest},
     types::PSNInner,
 };
@@ -74,6 +75,20 @@ impl PSNRequest for MyPSN {
         Box::pin(async move { Ok(()) })
     }
 
+    fn validate_tokens(
+        &self,
+        _client: &Self::Client,
+    ) -> Pin<Box<dyn Future<Output = Result<TokenValidation, Self::Error>> + Send>> {
+        Box::pin(async move { Err(MyError) })
+    }
+
+    fn logout(
+        &mut self,
+        _client: &Self::Client,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Self::Error>> + Send>> {
+        Box::pin(async move { Ok(()) })
+    }
+
     fn get_by_url_encode<'s, 'u: 's, T: DeserializeOwned + 'static>(
         &'s self,
         _client: &Self::Client,
@@ -118,6 +133,24 @@ impl PSNRequest for MyPSN {
         Box::pin(async move { Ok(()) })
     }
 
+    fn put_by_url_encode<'s, 'u: 's>(
+        &'s self,
+        _client: &Self::Client,
+        url: &'u str,
+        body: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Self::Error>> + Send>> {
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn post_by_url_encode<'s, 'u: 's>(
+        &'s self,
+        _client: &Self::Client,
+        url: &'u str,
+        body: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Self::Error>> + Send>> {
+        Box::pin(async move { Ok(()) })
+    }
+
     fn post_by_multipart<'s, 't: 's>(
         &'s self,
         _client: &Self::Client,
@@ -128,6 +161,14 @@ impl PSNRequest for MyPSN {
         Box::pin(async move { Ok(()) })
     }
 
+    fn get_bytes_by_url_encode<'s, 'u: 's>(
+        &'s self,
+        _client: &Self::Client,
+        url: &'u str,
+    ) -> Pin<Box<dyn Future<Output = Result<Attachment, Self::Error>> + Send>> {
+        Box::pin(async move { Err(MyError) })
+    }
+
     fn read_path(path: &str) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, Self::Error>> + Send>> {
         Box::pin(async move { Ok(vec![]) })
     }
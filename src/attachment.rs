@@ -0,0 +1,108 @@
+//! Image attachment checks for message threads.
+//!
+//! PSN only accepts PNG, JPEG and GIF images up to `MAX_IMAGE_SIZE` bytes.
+//! With `image-resize` feature enabled `fit_image` can downscale and re-encode an oversized image to JPEG.
+//! Attachments of received messages can be downloaded with `PSN::download_attachment`.
+use derive_more::Display;
+
+/// The max image size accepted by PSN message threads in bytes.
+pub const MAX_IMAGE_SIZE: usize = 5 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl ImageFormat {
+    /// Detect the format from the magic bytes of `buf`. Return `None` when it's not a supported image.
+    pub fn sniff(buf: &[u8]) -> Option<Self> {
+        if buf.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if buf.starts_with(b"\xff\xd8\xff") {
+            Some(ImageFormat::Jpeg)
+        } else if buf.starts_with(b"GIF87a") || buf.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else {
+            None
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif => "image/gif",
+        }
+    }
+}
+
+/// A downloaded message attachment.
+#[derive(Debug)]
+pub struct Attachment {
+    /// The `Content-Type` header from PSN. `None` when PSN doesn't provide it.
+    pub content_type: Option<String>,
+    pub buf: Vec<u8>,
+}
+
+#[derive(Debug, Display)]
+pub enum AttachmentError {
+    #[display(fmt = "Message event has no attachment")]
+    Missing,
+    #[display(fmt = "Attachment is not a PNG, JPEG or GIF image")]
+    Unsupported,
+    #[display(
+        fmt = "Attachment is {} bytes and larger than the max size {} bytes",
+        _0,
+        MAX_IMAGE_SIZE
+    )]
+    TooLarge(usize),
+    #[display(fmt = "Failed to resize attachment: {}", _0)]
+    Resize(Box<str>),
+}
+
+/// Check `buf` is a supported image within `MAX_IMAGE_SIZE` and return its format.
+pub fn check_image(buf: &[u8]) -> Result<ImageFormat, AttachmentError> {
+    let format = ImageFormat::sniff(buf).ok_or(AttachmentError::Unsupported)?;
+
+    if buf.len() > MAX_IMAGE_SIZE {
+        return Err(AttachmentError::TooLarge(buf.len()));
+    }
+
+    Ok(format)
+}
+
+/// Downscale and re-encode `buf` to JPEG until it fits in `MAX_IMAGE_SIZE`.
+/// `buf` is returned as is when it already fits. Animated GIF would lose its animation when resized.
+#[cfg(feature = "image-resize")]
+pub fn fit_image(buf: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>, AttachmentError> {
+    use image::GenericImageView;
+    use std::borrow::Cow;
+
+    match check_image(buf) {
+        Ok(_) => return Ok(Cow::Borrowed(buf)),
+        Err(AttachmentError::TooLarge(_)) => (),
+        Err(e) => return Err(e),
+    }
+
+    let img =
+        image::load_from_memory(buf).map_err(|e| AttachmentError::Resize(e.to_string().into()))?;
+
+    let (mut width, mut height) = img.dimensions();
+
+    // shrink 25% on every try. a JPEG of a few pixels is always small enough.
+    loop {
+        let mut out = Vec::new();
+        img.resize(width, height, image::imageops::FilterType::Triangle)
+            .write_to(&mut out, image::ImageOutputFormat::Jpeg(85))
+            .map_err(|e| AttachmentError::Resize(e.to_string().into()))?;
+
+        if out.len() <= MAX_IMAGE_SIZE || width <= 1 || height <= 1 {
+            return check_image(&out).map(|_| Cow::Owned(out));
+        }
+
+        width = width * 3 / 4;
+        height = height * 3 / 4;
+    }
+}
@@ -0,0 +1,308 @@
+//! Encrypted at rest credential file for storing multiple `PSNInner` credentials.
+//!
+//! Only `email`, `region`, `language`, `self_online_id`, `npsso` and `refresh_token` are stored.
+//! `access_token` expires quickly so you still need to call `PSNRequest::auth` on loaded `PSNInner`s.
+//!
+//! The file is encrypted with ChaCha20-Poly1305. The key is either a raw 32 bytes key or derived from a passphrase with PBKDF2-HMAC-SHA256.
+//!
+//! # Example:
+//!```no_run
+//!use psn_api_rs::{credential::{load_credentials, CredentialKey}, psn::{PSN, PSNError}, traits::PSNRequest};
+//!
+//!async fn reload_psn() -> Result<PSN, PSNError> {
+//!    let key = CredentialKey::Passphrase("your passphrase");
+//!    let client = PSN::new_client()?;
+//!
+//!    let mut inners = Vec::new();
+//!    for inner in load_credentials("credentials.bin", key).await?.into_iter() {
+//!        inners.push(inner.auth(client.clone()).await?);
+//!    }
+//!
+//!    let psn = PSN::new(inners).await;
+//!
+//!    // refresh tokens are rotated when authenticating so save them back.
+//!    psn.save_credentials("credentials.bin", key).await?;
+//!
+//!    Ok(psn)
+//!}
+//!```
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hmac::Hmac;
+use rand::Rng;
+use sha2::Sha256;
+use tokio::io::AsyncWriteExt;
+
+use crate::psn::PSNError;
+use crate::traits::EncodeUrl;
+use crate::types::PSNInner;
+
+const MAGIC: &[u8; 4] = b"PSNC";
+const VERSION: u8 = 1;
+const KDF_NONE: u8 = 0;
+const KDF_PBKDF2: u8 = 1;
+const PBKDF2_ROUNDS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+// magic + version + kdf + salt + nonce
+const HEADER_LEN: usize = 4 + 1 + 1 + SALT_LEN + NONCE_LEN;
+
+/// Key used to encrypt and decrypt the credential file.
+#[derive(Clone, Copy)]
+pub enum CredentialKey<'a> {
+    /// A passphrase that goes through PBKDF2-HMAC-SHA256 with a random salt.
+    Passphrase(&'a str),
+    /// A raw 32 bytes key. Use it when you already have a key from a secret manager.
+    Key(&'a [u8; 32]),
+}
+
+impl CredentialKey<'_> {
+    fn kdf(&self) -> u8 {
+        match self {
+            CredentialKey::Passphrase(_) => KDF_PBKDF2,
+            CredentialKey::Key(_) => KDF_NONE,
+        }
+    }
+
+    fn derive(&self, salt: &[u8]) -> [u8; 32] {
+        match self {
+            CredentialKey::Passphrase(pass) => {
+                let mut key = [0u8; 32];
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(pass.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+                key
+            }
+            CredentialKey::Key(key) => **key,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredCredential {
+    email: String,
+    region: String,
+    language: String,
+    self_online_id: String,
+    npsso: Option<String>,
+    refresh_token: Option<String>,
+}
+
+impl From<&PSNInner> for StoredCredential {
+    fn from(inner: &PSNInner) -> Self {
+        StoredCredential {
+            email: inner.get_email().to_owned(),
+            region: inner.region().to_owned(),
+            language: inner.language().to_owned(),
+            self_online_id: inner.self_online_id().to_owned(),
+            npsso: inner.npsso().map(String::from),
+            refresh_token: inner.get_refresh_token().map(String::from),
+        }
+    }
+}
+
+impl From<StoredCredential> for PSNInner {
+    fn from(c: StoredCredential) -> Self {
+        let mut inner = PSNInner::new();
+        inner
+            .set_email(c.email)
+            .set_region(c.region)
+            .set_lang(c.language)
+            .set_self_online_id(c.self_online_id)
+            .add_npsso(c.npsso.unwrap_or_default())
+            .add_refresh_token(c.refresh_token.unwrap_or_default());
+        inner
+    }
+}
+
+/// Encrypt the credentials of `inners` into the credential file format.
+pub fn encrypt_credentials(
+    key: CredentialKey<'_>,
+    inners: &[PSNInner],
+) -> Result<Vec<u8>, PSNError> {
+    let creds = inners
+        .iter()
+        .map(Into::into)
+        .collect::<Vec<StoredCredential>>();
+    let msg = serde_json::to_vec(&creds)
+        .map_err(|e| PSNError::InvalidCredentialFile(e.to_string().into()))?;
+
+    let mut rng = rand::thread_rng();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt);
+    rng.fill(&mut nonce);
+
+    let mut buf = Vec::with_capacity(HEADER_LEN + msg.len() + 16);
+    buf.extend_from_slice(MAGIC);
+    buf.push(VERSION);
+    buf.push(key.kdf());
+    buf.extend_from_slice(&salt);
+    buf.extend_from_slice(&nonce);
+
+    let cipher = ChaCha20Poly1305::new(&Key::from(key.derive(&salt)));
+    // header is authenticated as associated data.
+    let encrypted = cipher
+        .encrypt(
+            &Nonce::from(nonce),
+            Payload {
+                msg: &msg,
+                aad: &buf,
+            },
+        )
+        .map_err(|_| PSNError::InvalidCredentialFile("Failed to encrypt credentials".into()))?;
+
+    buf.extend_from_slice(&encrypted);
+
+    Ok(buf)
+}
+
+/// Decrypt the credential file format into `PSNInner`s.
+pub fn decrypt_credentials(key: CredentialKey<'_>, buf: &[u8]) -> Result<Vec<PSNInner>, PSNError> {
+    if buf.len() < HEADER_LEN || &buf[..4] != MAGIC {
+        return Err(PSNError::InvalidCredentialFile(
+            "Not a credential file".into(),
+        ));
+    }
+    if buf[4] != VERSION {
+        return Err(PSNError::InvalidCredentialFile(
+            format!("Unsupported version: {}", buf[4]).into(),
+        ));
+    }
+    if buf[5] != key.kdf() {
+        return Err(PSNError::InvalidCredentialFile(
+            "Key type doesn't match the one used for saving".into(),
+        ));
+    }
+
+    let (header, encrypted) = buf.split_at(HEADER_LEN);
+    let salt = &header[6..6 + SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&header[6 + SALT_LEN..]);
+
+    let cipher = ChaCha20Poly1305::new(&Key::from(key.derive(salt)));
+    let msg = cipher
+        .decrypt(
+            &Nonce::from(nonce),
+            Payload {
+                msg: encrypted,
+                aad: header,
+            },
+        )
+        .map_err(|_| {
+            PSNError::InvalidCredentialFile("Wrong key or the file is corrupted".into())
+        })?;
+
+    let creds: Vec<StoredCredential> = serde_json::from_slice(&msg)
+        .map_err(|e| PSNError::InvalidCredentialFile(e.to_string().into()))?;
+
+    Ok(creds.into_iter().map(Into::into).collect())
+}
+
+/// Read and decrypt a credential file. The returned `PSNInner`s have no `access_token` and need to be authenticated.
+pub async fn load_credentials(
+    path: impl AsRef<Path>,
+    key: CredentialKey<'_>,
+) -> Result<Vec<PSNInner>, PSNError> {
+    let buf = tokio::fs::read(path).await.map_err(PSNError::FromStd)?;
+    decrypt_credentials(key, &buf)
+}
+
+/// Encrypt and write a credential file. The file is written to a temporary path first and then renamed to `path`.
+pub async fn save_credentials(
+    path: impl AsRef<Path>,
+    key: CredentialKey<'_>,
+    inners: &[PSNInner],
+) -> Result<(), PSNError> {
+    let buf = encrypt_credentials(key, inners)?;
+
+    let path = path.as_ref();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+
+    // the file holds refresh tokens so it's only readable by the owner. a leftover temporary file could have other permissions.
+    let _ = tokio::fs::remove_file(&tmp).await;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = tokio::fs::OpenOptions::from(options)
+        .open(&tmp)
+        .await
+        .map_err(PSNError::FromStd)?;
+    file.write_all(&buf).await.map_err(PSNError::FromStd)?;
+    file.flush().await.map_err(PSNError::FromStd)?;
+    drop(file);
+
+    tokio::fs::rename(&tmp, path)
+        .await
+        .map_err(PSNError::FromStd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7u8; 32];
+
+    fn inner() -> PSNInner {
+        let mut inner = PSNInner::new();
+        inner
+            .set_email("a@example.com".into())
+            .set_region("us".into())
+            .add_npsso("npsso".into())
+            .add_refresh_token("refresh".into());
+        inner
+    }
+
+    fn err_msg(res: Result<Vec<PSNInner>, PSNError>) -> String {
+        match res {
+            Err(PSNError::InvalidCredentialFile(msg)) => msg.into(),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let buf = encrypt_credentials(CredentialKey::Key(&KEY), &[inner()]).unwrap();
+        let inners = decrypt_credentials(CredentialKey::Key(&KEY), &buf).unwrap();
+
+        assert_eq!(inners.len(), 1);
+        assert_eq!(inners[0].get_email(), "a@example.com");
+        assert_eq!(inners[0].region(), "us");
+        assert_eq!(inners[0].npsso(), Some("npsso"));
+        assert_eq!(inners[0].get_refresh_token(), Some("refresh"));
+    }
+
+    #[test]
+    fn wrong_key() {
+        let buf = encrypt_credentials(CredentialKey::Key(&KEY), &[inner()]).unwrap();
+        let res = decrypt_credentials(CredentialKey::Key(&[8u8; 32]), &buf);
+
+        assert_eq!(err_msg(res), "Wrong key or the file is corrupted");
+    }
+
+    #[test]
+    fn tampered_header() {
+        let mut buf = encrypt_credentials(CredentialKey::Key(&KEY), &[inner()]).unwrap();
+        // salt is not used by raw key so only the associated data check catches it.
+        buf[6] ^= 1;
+        let res = decrypt_credentials(CredentialKey::Key(&KEY), &buf);
+
+        assert_eq!(err_msg(res), "Wrong key or the file is corrupted");
+    }
+
+    #[test]
+    fn kdf_mismatch() {
+        let buf = encrypt_credentials(CredentialKey::Passphrase("pass"), &[inner()]).unwrap();
+        let res = decrypt_credentials(CredentialKey::Key(&KEY), &buf);
+
+        assert_eq!(
+            err_msg(res),
+            "Key type doesn't match the one used for saving"
+        );
+    }
+}
@@ -58,36 +58,60 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod attachment;
 pub mod metas;
 pub mod models;
 pub mod traits;
 pub mod types;
 
+#[cfg(all(feature = "default", feature = "credential-file"))]
+pub mod credential;
+#[cfg(feature = "default")]
+pub mod watcher;
+
 mod private_model;
 
 #[cfg(feature = "default")]
 pub mod psn {
+    use std::borrow::Cow;
+    use std::collections::{HashMap, HashSet};
     use std::future::Future;
-    use std::sync::{Mutex, MutexGuard};
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, MutexGuard};
     use std::time::Duration;
 
     use derive_more::Display;
-    use reqwest::{Client, ClientBuilder, Error, Proxy};
+    use futures::future::try_join;
+    use futures::stream::{self, StreamExt, TryStreamExt};
+    use reqwest::{Client, ClientBuilder, Error, Proxy, Response};
     use serde::de::DeserializeOwned;
     use tang_rs::{Builder, Manager, ManagerFuture, ManagerTimeout, Pool, PoolRef};
+    use tokio::io::AsyncWriteExt;
     use tokio::time::{delay_for, Delay};
 
-    use crate::models::MessageThreadNew;
-    use crate::traits::PSNRequest;
-    use crate::types::PSNInner;
+    use crate::attachment::{Attachment, AttachmentError};
+    use crate::models::{
+        MessageEventDetail, MessageThreadMembers, MessageThreadNew, MessageThreadsSummary,
+        NpServiceName, PSNUserV2, PrivacySettings, ProfileIds, StickerDetail, TitleComparison,
+        TrophyComparison, TrophyReport, TrophySet, TrophyTitle, TrophyTitles,
+    };
+    use crate::traits::{EncodeUrl, PSNRequest};
+    use crate::types::{
+        content_type, FriendQuery, FriendRequestType, PSNInner, Secret, TitleQuery,
+    };
 
     #[derive(Debug, Clone)]
     pub struct PSN {
         inner: Pool<PSNInnerManager>,
         client: Client,
         proxy_pool: Option<Pool<ProxyPoolManager>>,
+        thread_cache: ThreadCache,
     }
 
+    // (self_online_id, online_id) -> thread_id of 1:1 message thread. online ids are lowercased.
+    type ThreadCache = Arc<Mutex<HashMap<(String, String), String>>>;
+
     /// You can override `PSNRequest` trait to impl your own error type.
     #[derive(Debug, Display)]
     pub enum PSNError {
@@ -95,18 +119,68 @@ pub mod psn {
         NoClient,
         #[display(fmt = "No PSNInner object is available")]
         NoPSNInner,
+        #[display(fmt = "No npsso code is provided")]
+        NoNpsso,
+        /// The npsso code is redacted in `Debug` and `Display`. Use `Secret::expose` to get the raw value.
         #[display(fmt = "Failed to login in to PSN on npsso code: {}", _0)]
-        InvalidNpsso(Box<str>),
+        InvalidNpsso(Secret),
+        /// The refresh token is redacted in `Debug` and `Display`. Use `Secret::expose` to get the raw value.
         #[display(fmt = "Failed to login in to PSN on refresh token: {}", _0)]
-        InvalidRefresh(Box<str>),
+        InvalidRefresh(Secret),
         #[display(fmt = "Request to PSN pool is timeout.")]
         TimeOut,
         #[display(fmt = "Error from Reqwest http client: {}", _0)]
         FromReqwest(Error),
         #[display(fmt = "Error from PSN response: {}", _0)]
         FromPSN(Box<str>),
+        /// Error response of PSN api with its error code and message.
+        #[display(fmt = "Error from PSN response: {} (code {})", _1, _0)]
+        FromPSNResponse(u32, Box<str>),
         #[display(fmt = "Error from IO: {}", _0)]
         FromStd(std::io::Error),
+        #[display(fmt = "Invalid credential file: {}", _0)]
+        InvalidCredentialFile(Box<str>),
+        #[display(fmt = "Invalid attachment: {}", _0)]
+        InvalidAttachment(AttachmentError),
+        #[display(fmt = "Message has neither text nor attachment")]
+        EmptyMessage,
+        #[display(fmt = "Group thread has no member other than the logged in account")]
+        NoThreadMember,
+    }
+
+    impl From<AttachmentError> for PSNError {
+        fn from(e: AttachmentError) -> Self {
+            PSNError::InvalidAttachment(e)
+        }
+    }
+
+    // PSN api error codes.
+    const USER_NOT_FOUND: u32 = 2_105_356;
+    const RESOURCE_NOT_FOUND: u32 = 2_240_525;
+    const ACCESS_CONTROL: u32 = 2_240_526;
+
+    /// Per online id result of `PSN::get_profiles`.
+    #[derive(Debug)]
+    pub enum ProfileLookup<T> {
+        Found(T),
+        NotFound,
+        /// The user's privacy settings don't allow the access.
+        Private,
+        Error(PSNError),
+    }
+
+    impl<T> From<Result<T, PSNError>> for ProfileLookup<T> {
+        fn from(res: Result<T, PSNError>) -> Self {
+            match res {
+                Ok(t) => ProfileLookup::Found(t),
+                Err(PSNError::FromPSNResponse(code, msg)) => match code {
+                    USER_NOT_FOUND | RESOURCE_NOT_FOUND => ProfileLookup::NotFound,
+                    ACCESS_CONTROL => ProfileLookup::Private,
+                    _ => ProfileLookup::Error(PSNError::FromPSNResponse(code, msg)),
+                },
+                Err(e) => ProfileLookup::Error(e),
+            }
+        }
     }
 
     impl From<()> for PSNError {
@@ -118,6 +192,8 @@ pub mod psn {
     pub struct PSNInnerManager {
         inner: Mutex<Vec<PSNInner>>,
         client: Client,
+        // the max size of PSNInner pool. used to bound the concurrency of fan out requests.
+        max_size: AtomicUsize,
     }
 
     impl PSNInnerManager {
@@ -127,6 +203,7 @@ pub mod psn {
                 client: ClientBuilder::new()
                     .build()
                     .expect("Failed to build http client for PSNInnerManager"),
+                max_size: AtomicUsize::new(0),
             }
         }
 
@@ -134,6 +211,10 @@ pub mod psn {
             self.inner.lock().unwrap()
         }
 
+        fn take_psn_inner(&self) -> Vec<PSNInner> {
+            std::mem::take(&mut *self.get_psn_inner())
+        }
+
         fn add_psn_inner(&self, psn_inner: Vec<PSNInner>) {
             let mut inners = self.get_psn_inner();
             for psn in psn_inner.into_iter() {
@@ -295,6 +376,7 @@ pub mod psn {
             let size = psn_inner.len();
 
             mgr.add_psn_inner(psn_inner);
+            mgr.max_size.store(size, Ordering::Relaxed);
 
             let inner_pool = Builder::new()
                 .always_check(true)
@@ -310,6 +392,7 @@ pub mod psn {
                 inner: inner_pool,
                 client: Self::new_client().expect("Failed to build http client"),
                 proxy_pool: None,
+                thread_cache: Default::default(),
             }
         }
 
@@ -322,6 +405,10 @@ pub mod psn {
 
         pub fn set_psn_inner_max(&self, max_size: usize) {
             self.inner.set_max_size(max_size);
+            self.inner
+                .get_manager()
+                .max_size
+                .store(max_size, Ordering::Relaxed);
         }
 
         pub fn pause_inner(&self) {
@@ -336,6 +423,25 @@ pub mod psn {
             self.inner.clear();
         }
 
+        /// The same as `clear_inner` but also revoke the tokens of all PSNInners on PSN server.
+        ///
+        /// PSNInners in use by other tasks are waited to be returned to pool.
+        /// All PSNInners are tried and the last error(if any) is returned.
+        pub async fn clear_inner_with_revoke(&self) -> Result<(), PSNError> {
+            let inners = self.all_inners().await?;
+            self.inner.get_manager().take_psn_inner();
+
+            self.inner.clear();
+
+            let mut result = Ok(());
+            for mut inner in inners.into_iter() {
+                if let Err(e) = inner.logout(&self.client).await {
+                    result = Err(e);
+                }
+            }
+            result
+        }
+
         /// Add http proxy pool to combat PSN rate limiter. This is not required.
         ///# Example:
         ///```no_run
@@ -405,6 +511,145 @@ pub mod psn {
             psn_inner.get_profile(&client, online_id).await
         }
 
+        /// Fetch the profiles of multiple users. The result is in the same order as `online_ids`.
+        ///
+        /// PSN has no bulk profile endpoint for online ids so one request is made for every id.
+        /// Requests are fanned out across the PSNInner(and proxy) pool and at most one request per pooled PSNInner is in flight.
+        /// Errors of one id don't stop the others.
+        pub async fn get_profiles<T: DeserializeOwned + 'static>(
+            &self,
+            online_ids: &[&str],
+        ) -> Vec<(String, ProfileLookup<T>)> {
+            stream::iter(online_ids.iter())
+                .map(|online_id| async move {
+                    let res = self.get_profile(online_id).await;
+                    ((*online_id).to_owned(), res.into())
+                })
+                .buffered(self.concurrency())
+                .collect()
+                .await
+        }
+
+        pub async fn get_presence<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_presence(&client, online_id).await
+        }
+
+        pub async fn get_activity_feed<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+            page: u32,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_activity_feed(&client, online_id, page).await
+        }
+
+        pub async fn get_news_feed<T: DeserializeOwned + 'static>(
+            &self,
+            page: u32,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_news_feed(&client, page).await
+        }
+
+        pub async fn get_account_id<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_account_id(&client, online_id).await
+        }
+
+        pub async fn get_profile_by_account_id<T: DeserializeOwned + 'static>(
+            &self,
+            account_id: &str,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_profile_by_account_id(&client, account_id)
+                .await
+        }
+
+        /// A shortcut for `get_account_id` returns the account id only.
+        pub async fn resolve_account_id(&self, online_id: &str) -> Result<String, PSNError> {
+            let ids: ProfileIds = self.get_account_id(online_id).await?;
+            Ok(ids.profile.account_id)
+        }
+
+        /// A shortcut for `get_profile_by_account_id` returns the current online id only.
+        pub async fn resolve_online_id(&self, account_id: &str) -> Result<String, PSNError> {
+            let user: PSNUserV2 = self.get_profile_by_account_id(account_id).await?;
+            Ok(user.online_id)
+        }
+
+        pub async fn get_friends<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+            query: &FriendQuery,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_friends(&client, online_id, query).await
+        }
+
+        pub async fn get_friend_requests<T: DeserializeOwned + 'static>(
+            &self,
+            request_type: FriendRequestType,
+            query: &FriendQuery,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_friend_requests(&client, request_type, query)
+                .await
+        }
+
+        pub async fn get_privacy_settings<T: DeserializeOwned + 'static>(
+            &self,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_privacy_settings(&client).await
+        }
+
+        pub async fn update_privacy_settings(
+            &self,
+            settings: &PrivacySettings,
+        ) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.update_privacy_settings(&client, settings).await
+        }
+
+        pub async fn get_block_list<T: DeserializeOwned + 'static>(
+            &self,
+            offset: u32,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_block_list(&client, offset).await
+        }
+
+        pub async fn block_user(&self, online_id: &str) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.block_user(&client, online_id).await
+        }
+
+        pub async fn unblock_user(&self, online_id: &str) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.unblock_user(&client, online_id).await
+        }
+
         pub async fn get_titles<T: DeserializeOwned + 'static>(
             &self,
             online_id: &str,
@@ -415,6 +660,51 @@ pub mod psn {
             psn_inner.get_titles(&client, online_id, offset).await
         }
 
+        /// Return one page of titles. `TitleQuery::sort` is ignored as PSN has no server side sort.
+        pub async fn get_titles_with_query<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+            query: &TitleQuery,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_titles_with_query(&client, online_id, query)
+                .await
+        }
+
+        /// Collect all title pages of `online_id` starting from the query offset and sort them with `TitleQuery::sort`.
+        /// `TitleQuery::limit` is the page size.
+        ///
+        ///# Example:
+        ///```no_run
+        ///use psn_api_rs::{psn::PSN, models::{TitleSort, TrophyTitle}, types::{IconSize, Platform, TitleQuery}};
+        ///
+        ///async fn ps4_titles(psn: &PSN) -> Vec<TrophyTitle> {
+        ///    let query = TitleQuery::new()
+        ///        .platforms(vec![Platform::PS4])
+        ///        .icon_size(IconSize::Large)
+        ///        .sort(TitleSort::Name);
+        ///
+        ///    psn.get_all_titles("Hakoom", &query)
+        ///        .await
+        ///        .expect("Failed to get titles")
+        ///}
+        ///```
+        pub async fn get_all_titles(
+            &self,
+            online_id: &str,
+            query: &TitleQuery,
+        ) -> Result<Vec<TrophyTitle>, PSNError> {
+            let mut titles = self.all_titles(online_id, query).await?;
+
+            if let Some(sort) = query.get_sort() {
+                sort.apply(&mut titles);
+            }
+
+            Ok(titles)
+        }
+
         pub async fn get_trophy_set<T: DeserializeOwned + 'static>(
             &self,
             online_id: &str,
@@ -427,6 +717,162 @@ pub mod psn {
                 .await
         }
 
+        pub async fn get_trophy_groups<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+            np_communication_id: &str,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_trophy_groups(&client, online_id, np_communication_id)
+                .await
+        }
+
+        pub async fn get_trophy_group_set<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+            np_communication_id: &str,
+            group_id: &str,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_trophy_group_set(&client, online_id, np_communication_id, group_id)
+                .await
+        }
+
+        pub async fn get_titles_v2<T: DeserializeOwned + 'static>(
+            &self,
+            account_id: &str,
+            offset: u32,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.get_titles_v2(&client, account_id, offset).await
+        }
+
+        pub async fn get_trophy_groups_v2<T: DeserializeOwned + 'static>(
+            &self,
+            np_communication_id: &str,
+            np_service_name: NpServiceName,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_trophy_groups_v2(&client, np_communication_id, np_service_name)
+                .await
+        }
+
+        pub async fn get_user_trophy_groups_v2<T: DeserializeOwned + 'static>(
+            &self,
+            account_id: &str,
+            np_communication_id: &str,
+            np_service_name: NpServiceName,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_user_trophy_groups_v2(
+                    &client,
+                    account_id,
+                    np_communication_id,
+                    np_service_name,
+                )
+                .await
+        }
+
+        pub async fn get_trophies_v2<T: DeserializeOwned + 'static>(
+            &self,
+            np_communication_id: &str,
+            np_service_name: NpServiceName,
+            group_id: &str,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_trophies_v2(&client, np_communication_id, np_service_name, group_id)
+                .await
+        }
+
+        pub async fn get_user_trophies_v2<T: DeserializeOwned + 'static>(
+            &self,
+            account_id: &str,
+            np_communication_id: &str,
+            np_service_name: NpServiceName,
+            group_id: &str,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .get_user_trophies_v2(
+                    &client,
+                    account_id,
+                    np_communication_id,
+                    np_service_name,
+                    group_id,
+                )
+                .await
+        }
+
+        /// Collect all trophy titles and their trophy sets of `online_id` and aggregate them into a `TrophyReport`.
+        ///
+        /// Title pages and trophy sets are fetched concurrently with at most one request per pooled PSNInner in flight.
+        pub async fn trophy_report(&self, online_id: &str) -> Result<TrophyReport, PSNError> {
+            let titles = self.all_titles(online_id, &TitleQuery::new()).await?;
+
+            let sets = stream::iter(titles.iter())
+                .map(|title| {
+                    self.get_trophy_set::<TrophySet>(online_id, &title.np_communication_id)
+                })
+                .buffered(self.concurrency())
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            Ok(TrophyReport::new(
+                online_id,
+                titles.into_iter().zip(sets).collect(),
+            ))
+        }
+
+        /// Compare the trophies of one title between two users. Both trophy sets are fetched concurrently.
+        pub async fn compare_trophies(
+            &self,
+            online_id_a: &str,
+            online_id_b: &str,
+            np_communication_id: &str,
+        ) -> Result<TrophyComparison, PSNError> {
+            let (set_a, set_b) = try_join(
+                self.get_trophy_set::<TrophySet>(online_id_a, np_communication_id),
+                self.get_trophy_set::<TrophySet>(online_id_b, np_communication_id),
+            )
+            .await?;
+
+            Ok(TrophyComparison::new(np_communication_id, set_a, set_b))
+        }
+
+        /// Compare the trophy title libraries of two users.
+        /// Titles only one of the users has played are included with the other user's detail as `None`.
+        pub async fn compare_titles(
+            &self,
+            online_id_a: &str,
+            online_id_b: &str,
+        ) -> Result<TitleComparison, PSNError> {
+            let query = TitleQuery::new();
+            let (titles_a, titles_b) = try_join(
+                self.all_titles(online_id_a, &query),
+                self.all_titles(online_id_b, &query),
+            )
+            .await?;
+
+            Ok(TitleComparison::new(
+                online_id_a,
+                titles_a,
+                online_id_b,
+                titles_b,
+            ))
+        }
+
         pub async fn get_message_threads<T: DeserializeOwned + 'static>(
             &self,
             offset: u32,
@@ -451,38 +897,260 @@ pub mod psn {
             psn_inner.leave_message_thread(&client, thread_id).await
         }
 
-        pub async fn send_message<T: DeserializeOwned + 'static>(
+        /// Generate a group thread with `online_ids` and the `self_online_id` of the PSNInner used for the request.
+        /// `online_ids` must have at least one user other than the `self_online_id` or `PSNError::NoThreadMember` is returned.
+        pub async fn generate_group_thread<T: DeserializeOwned + 'static>(
+            &self,
+            online_ids: &[&str],
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            if online_ids
+                .iter()
+                .all(|id| id.eq_ignore_ascii_case(psn_inner.self_online_id()))
+            {
+                return Err(PSNError::NoThreadMember);
+            }
+
+            psn_inner.generate_group_thread(&client, online_ids).await
+        }
+
+        pub async fn add_thread_members(
+            &self,
+            thread_id: &str,
+            online_ids: &[&str],
+        ) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .add_thread_members(&client, thread_id, online_ids)
+                .await
+        }
+
+        pub async fn remove_thread_member(
+            &self,
+            thread_id: &str,
+            online_id: &str,
+        ) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .remove_thread_member(&client, thread_id, online_id)
+                .await
+        }
+
+        pub async fn set_thread_name(
+            &self,
+            thread_id: &str,
+            thread_name: &str,
+        ) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .set_thread_name(&client, thread_id, thread_name)
+                .await
+        }
+
+        /// Send a sticker to `online_id` in the existing 1:1 thread with the user. A new thread is generated only when there is none.
+        pub async fn send_sticker<T: DeserializeOwned + 'static>(
             &self,
             online_id: &str,
+            sticker: &StickerDetail,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            let thread_id = self.resolve_thread(&client, &psn_inner, online_id).await?;
+
+            let res = psn_inner.send_sticker(&client, sticker, &thread_id).await;
+            if res.is_err() {
+                self.forget_thread(&psn_inner, online_id);
+            }
+            res
+        }
+
+        pub async fn send_sticker_to_thread<T: DeserializeOwned + 'static>(
+            &self,
+            thread_id: &str,
+            sticker: &StickerDetail,
+        ) -> Result<T, PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.send_sticker(&client, sticker, thread_id).await
+        }
+
+        pub async fn mark_thread_read(&self, thread_id: &str) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner.mark_thread_read(&client, thread_id).await
+        }
+
+        pub async fn set_thread_favorite(
+            &self,
+            thread_id: &str,
+            favorite: bool,
+        ) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .set_thread_favorite(&client, thread_id, favorite)
+                .await
+        }
+
+        pub async fn set_thread_notification(
+            &self,
+            thread_id: &str,
+            push_notification: bool,
+        ) -> Result<(), PSNError> {
+            let (client, psn_inner) = self.get().await?;
+
+            psn_inner
+                .set_thread_notification(&client, thread_id, push_notification)
+                .await
+        }
+
+        /// Send message into an existing thread without generating a new one.
+        /// At least one of `msg` and `path` must be `Some` or `PSNError::EmptyMessage` is returned.
+        pub async fn send_message_to_thread<T: DeserializeOwned + 'static>(
+            &self,
+            thread_id: &str,
             msg: Option<&str>,
             path: Option<&str>,
         ) -> Result<T, PSNError> {
-            let (client, psn_inner) = self.get().await?;
+            if msg.is_none() && path.is_none() {
+                return Err(PSNError::EmptyMessage);
+            }
 
-            let thread: MessageThreadNew = psn_inner
-                .generate_message_thread(&client, online_id)
-                .await?;
+            let buf = read_attachment(path).await?;
+
+            self.send_message_to_thread_with_buf(thread_id, msg, buf.as_deref())
+                .await
+        }
+
+        /// Send message into an existing thread without generating a new one.
+        /// At least one of `msg` and `buf` must be `Some` or `PSNError::EmptyMessage` is returned.
+        /// `buf` must be a PNG, JPEG or GIF image. Oversized image is resized with `image-resize` feature enabled.
+        pub async fn send_message_to_thread_with_buf<T: DeserializeOwned + 'static>(
+            &self,
+            thread_id: &str,
+            msg: Option<&str>,
+            buf: Option<&[u8]>,
+        ) -> Result<T, PSNError> {
+            if msg.is_none() && buf.is_none() {
+                return Err(PSNError::EmptyMessage);
+            }
+
+            let buf = prepare_attachment(buf)?;
+
+            let (client, psn_inner) = self.get().await?;
 
             psn_inner
-                .send_message(&client, online_id, msg, path, &thread.thread_id)
+                .send_message_with_buf(&client, "", msg, buf.as_deref(), thread_id)
+                .await
+        }
+
+        /// Send message to `online_id` in the existing 1:1 thread with the user. A new thread is generated only when there is none.
+        /// The thread is looked up once and cached. See `PSN::clear_thread_cache`.
+        /// At least one of `msg` and `path` must be `Some` or `PSNError::EmptyMessage` is returned.
+        pub async fn send_message<T: DeserializeOwned + 'static>(
+            &self,
+            online_id: &str,
+            msg: Option<&str>,
+            path: Option<&str>,
+        ) -> Result<T, PSNError> {
+            if msg.is_none() && path.is_none() {
+                return Err(PSNError::EmptyMessage);
+            }
+
+            let buf = read_attachment(path).await?;
+
+            self.send_message_with_buf(online_id, msg, buf.as_deref())
                 .await
         }
 
+        /// Send message to `online_id` in the existing 1:1 thread with the user. A new thread is generated only when there is none.
+        /// The thread is looked up once and cached. See `PSN::clear_thread_cache`.
+        /// At least one of `msg` and `buf` must be `Some` or `PSNError::EmptyMessage` is returned.
+        /// `buf` must be a PNG, JPEG or GIF image. Oversized image is resized with `image-resize` feature enabled.
         pub async fn send_message_with_buf<T: DeserializeOwned + 'static>(
             &self,
             online_id: &str,
             msg: Option<&str>,
             buf: Option<&[u8]>,
         ) -> Result<T, PSNError> {
+            if msg.is_none() && buf.is_none() {
+                return Err(PSNError::EmptyMessage);
+            }
+
+            let buf = prepare_attachment(buf)?;
+
             let (client, psn_inner) = self.get().await?;
 
-            let thread: MessageThreadNew = psn_inner
-                .generate_message_thread(&client, online_id)
-                .await?;
+            let thread_id = self.resolve_thread(&client, &psn_inner, online_id).await?;
+
+            let res = psn_inner
+                .send_message_with_buf(&client, online_id, msg, buf.as_deref(), &thread_id)
+                .await;
+            if res.is_err() {
+                self.forget_thread(&psn_inner, online_id);
+            }
+            res
+        }
+
+        /// Download the attachment of a message event in thread `thread_id`.
+        pub async fn download_attachment(
+            &self,
+            thread_id: &str,
+            event: &MessageEventDetail,
+        ) -> Result<Attachment, PSNError> {
+            let content_key = event
+                .attachment_content_key()
+                .ok_or(AttachmentError::Missing)?;
+
+            let (client, psn_inner) = self.get().await?;
 
             psn_inner
-                .send_message_with_buf(&client, online_id, msg, buf, &thread.thread_id)
+                .download_attachment(&client, thread_id, &event.event_index, content_key)
+                .await
+        }
+
+        /// Download the attachment of a message event in thread `thread_id` and write it to `path` chunk by chunk.
+        /// The file is written to a temporary path first and then renamed to `path`.
+        /// Return the content type of the attachment.
+        pub async fn download_attachment_to_path(
+            &self,
+            thread_id: &str,
+            event: &MessageEventDetail,
+            path: impl AsRef<Path>,
+        ) -> Result<Option<String>, PSNError> {
+            let content_key = event
+                .attachment_content_key()
+                .ok_or(AttachmentError::Missing)?;
+
+            let (client, psn_inner) = self.get().await?;
+
+            let url =
+                psn_inner.message_attachment_encode(thread_id, &event.event_index, content_key);
+            let mut res = psn_inner.bytes_response(&client, &url).await?;
+
+            let path = path.as_ref();
+            let mut tmp = path.as_os_str().to_owned();
+            tmp.push(".tmp");
+
+            // don't leave a partial file behind when the download failed.
+            if let Err(e) = write_chunks(&mut res, Path::new(&tmp)).await {
+                let _ = tokio::fs::remove_file(&tmp).await;
+                return Err(e);
+            }
+            tokio::fs::rename(&tmp, path)
                 .await
+                .map_err(PSNError::FromStd)?;
+
+            Ok(content_type(&res))
+        }
+
+        /// Clear the cached 1:1 threads used by `send_message` and `send_message_with_buf`.
+        pub fn clear_thread_cache(&self) {
+            self.thread_cache.lock().unwrap().clear();
         }
 
         pub async fn search_store_items<T: DeserializeOwned + 'static>(
@@ -503,6 +1171,170 @@ pub mod psn {
             self.inner.clone()
         }
 
+        /// Save the latest credentials of all PSNInners in pool and manager to an encrypted file.
+        /// PSNInners in use by other tasks are waited to be returned to pool so no account is left out.
+        #[cfg(feature = "credential-file")]
+        pub async fn save_credentials(
+            &self,
+            path: impl AsRef<std::path::Path>,
+            key: crate::credential::CredentialKey<'_>,
+        ) -> Result<(), PSNError> {
+            let inners = self.all_inners().await?;
+
+            crate::credential::save_credentials(path, key, &inners).await
+        }
+
+        // find the 1:1 thread between `psn_inner` account and `online_id` from cache or message threads of the account.
+        // generate a new thread if there is none.
+        // all 1:1 threads found when looking up are cached.
+        async fn resolve_thread(
+            &self,
+            client: &Client,
+            psn_inner: &PSNInner,
+            online_id: &str,
+        ) -> Result<String, PSNError> {
+            let self_id = psn_inner.self_online_id().to_ascii_lowercase();
+            let key = (self_id.clone(), online_id.to_ascii_lowercase());
+
+            if let Some(thread_id) = self.thread_cache.lock().unwrap().get(&key) {
+                return Ok(thread_id.to_owned());
+            }
+
+            // the members of cached threads are known already so they are not fetched again.
+            let cached = self
+                .thread_cache
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|((id, _), _)| id == &self_id)
+                .map(|(_, thread_id)| thread_id.to_owned())
+                .collect::<HashSet<_>>();
+
+            let mut offset = 0;
+            loop {
+                let summary: MessageThreadsSummary =
+                    psn_inner.get_message_threads(client, offset).await?;
+
+                // group threads are skipped before fetching their members.
+                for thread in summary
+                    .threads
+                    .iter()
+                    .filter(|t| t.is_one_on_one() && !cached.contains(&t.thread_id))
+                {
+                    let members: MessageThreadMembers = psn_inner
+                        .get_message_thread_members(client, &thread.thread_id)
+                        .await?;
+
+                    if members.thread_members.len() != 2 {
+                        continue;
+                    }
+
+                    let other = members
+                        .thread_members
+                        .iter()
+                        .map(|m| m.online_id.to_ascii_lowercase())
+                        .find(|id| id != &self_id);
+
+                    if let Some(other) = other {
+                        let found = other == key.1;
+
+                        self.thread_cache
+                            .lock()
+                            .unwrap()
+                            .insert((self_id.clone(), other), thread.thread_id.clone());
+
+                        if found {
+                            return Ok(thread.thread_id.clone());
+                        }
+                    }
+                }
+
+                offset += summary.threads.len() as u32;
+                if summary.threads.is_empty() || offset >= summary.total_size {
+                    break;
+                }
+            }
+
+            let thread: MessageThreadNew =
+                psn_inner.generate_message_thread(client, online_id).await?;
+
+            self.thread_cache
+                .lock()
+                .unwrap()
+                .insert(key, thread.thread_id.clone());
+
+            Ok(thread.thread_id)
+        }
+
+        // the cached thread could be gone(left or deleted) so look it up again on next send.
+        fn forget_thread(&self, psn_inner: &PSNInner, online_id: &str) {
+            let key = (
+                psn_inner.self_online_id().to_ascii_lowercase(),
+                online_id.to_ascii_lowercase(),
+            );
+            self.thread_cache.lock().unwrap().remove(&key);
+        }
+
+        // at most one request per pooled PSNInner is in flight when fanning out requests.
+        fn concurrency(&self) -> usize {
+            self.inner
+                .get_manager()
+                .max_size
+                .load(Ordering::Relaxed)
+                .max(1)
+        }
+
+        // fetch all trophy title pages of `online_id` concurrently.
+        async fn all_titles(
+            &self,
+            online_id: &str,
+            query: &TitleQuery,
+        ) -> Result<Vec<TrophyTitle>, PSNError> {
+            let first: TrophyTitles = self.get_titles_with_query(online_id, query).await?;
+
+            let start = query.get_offset() + first.trophy_titles.len() as u32;
+            let pages = (start..first.total_results)
+                .step_by(query.get_limit() as usize)
+                .map(|offset| {
+                    let query = query.clone().offset(offset);
+                    async move {
+                        self.get_titles_with_query::<TrophyTitles>(online_id, &query)
+                            .await
+                    }
+                });
+
+            let pages = stream::iter(pages)
+                .buffered(self.concurrency())
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            let mut titles = first.trophy_titles;
+            for page in pages.into_iter() {
+                titles.extend(page.trophy_titles);
+            }
+
+            Ok(titles)
+        }
+
+        // clone all PSNInners in pool and the backup ones in manager.
+        // wait for the ones in use by other tasks and error on pool timeout.
+        async fn all_inners(&self) -> Result<Vec<PSNInner>, PSNError> {
+            // hold all the refs so we don't get the same PSNInner twice.
+            let mut refs = Vec::new();
+            while refs.len() < usize::from(self.inner.state().connections) {
+                refs.push(self.inner.get().await?);
+            }
+
+            // read manager after holding refs. `Pool::get` could pop PSNInner from manager for a new connection.
+            let mut inners = refs
+                .iter()
+                .map(|inner_ref| (**inner_ref).clone())
+                .collect::<Vec<_>>();
+            inners.extend(self.inner.get_manager().get_psn_inner().iter().cloned());
+
+            Ok(inners)
+        }
+
         async fn get(&self) -> Result<(Client, PoolRef<'_, PSNInnerManager>), PSNError> {
             let proxy_ref = self.get_proxy_cli().await?;
             let inner_ref = self.inner.get().await?;
@@ -526,4 +1358,35 @@ pub mod psn {
             Ok(Some(pool_ref))
         }
     }
+
+    async fn read_attachment(path: Option<&str>) -> Result<Option<Vec<u8>>, PSNError> {
+        match path {
+            Some(path) => Ok(Some(PSNInner::read_path(path).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn write_chunks(res: &mut Response, path: &Path) -> Result<(), PSNError> {
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .map_err(PSNError::FromStd)?;
+        while let Some(chunk) = res.chunk().await? {
+            file.write_all(&chunk).await.map_err(PSNError::FromStd)?;
+        }
+        file.flush().await.map_err(PSNError::FromStd)
+    }
+
+    fn prepare_attachment(buf: Option<&[u8]>) -> Result<Option<Cow<'_, [u8]>>, PSNError> {
+        let buf = match buf {
+            Some(buf) => buf,
+            None => return Ok(None),
+        };
+
+        #[cfg(feature = "image-resize")]
+        let buf = crate::attachment::fit_image(buf)?;
+        #[cfg(not(feature = "image-resize"))]
+        let buf = crate::attachment::check_image(buf).map(|_| Cow::Borrowed(buf))?;
+
+        Ok(Some(buf))
+    }
 }
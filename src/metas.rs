@@ -2,13 +2,21 @@
 pub mod meta {
     pub const OAUTH_TOKEN_ENTRY: &str =
         "https://auth.api.sonyentertainmentnetwork.com/2.0/oauth/token";
+    pub const SSO_COOKIE_ENTRY: &str =
+        "https://auth.api.sonyentertainmentnetwork.com/2.0/ssocookie";
 
     pub const USERS_ENTRY: &str = "-prof.np.community.playstation.net/userProfile/v1/users/";
     pub const USER_TROPHY_ENTRY: &str = "-tpy.np.community.playstation.net/trophy/v1/trophyTitles/";
+    /// newer profile api keyed by account id.
+    pub const USER_PROFILE_V2_ENTRY: &str =
+        "https://m.np.playstation.com/api/userProfile/v1/internal/users/";
+    /// newer trophy api with PS5 support. It's keyed by account id and not region based.
+    pub const TROPHY_V2_ENTRY: &str = "https://m.np.playstation.com/api/trophy/v1/";
     pub const MESSAGE_THREAD_ENTRY: &str =
         "-gmsg.np.community.playstation.net/groupMessaging/v1/threads";
     pub const STORE_ENTRY: &str = "https://store.playstation.com/valkyrie-api/";
-    //const ACTIVITY_ENTRY: &'static str = "https://activity.api.np.km.playstation.net/activity/api/";
+    pub const ACTIVITY_ENTRY: &str =
+        "https://activity.api.np.km.playstation.net/activity/api/v1/users/";
 
     pub const CLIENT_ID: &str = "7c01ce37-cb6b-4938-9c1b-9e36fd5477fa";
     pub const CLIENT_SECRET: &str = "GNumO5QMsagNcO2q";
@@ -1,6 +1,87 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// string enums of PSN response. the first string of a variant is used by `as_str` and serializing and the others are aliases.
+// strings are matched case insensitively and an unknown one is kept as raw string in `Unknown`.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $str:literal $(| $alias:literal)*,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $str,)+
+                    $name::Unknown(s) => s.as_str(),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                $(
+                    if s.eq_ignore_ascii_case($str) $(|| s.eq_ignore_ascii_case($alias))* {
+                        return $name::$variant;
+                    }
+                )+
+                $name::Unknown(s)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Into::into)
+            }
+        }
+    };
+}
+
 /// `models` are used to deserialize psn response json.
 /// Some response fields are ignored so if you need more/less fields you can use your own struct as long as it impl `serde::Deserialize`.
 
+///The response type of `validate_tokens()`
+/// A field is `None` when the credential is not set or it's rejected by PSN.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenValidation {
+    pub access_token: Option<TokenInfo>,
+    pub refresh_token: Option<TokenInfo>,
+    pub npsso: Option<NpssoInfo>,
+}
+
+/// `scopes` are separated by space and `expiration` is an UTC date string.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TokenInfo {
+    pub scopes: Option<String>,
+    pub expiration: Option<String>,
+    pub user_id: Option<String>,
+    pub client_id: Option<String>,
+    pub grant_type: Option<String>,
+}
+
+/// `expires_in` is in seconds.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NpssoInfo {
+    pub expires_in: Option<u64>,
+    pub user_id: Option<String>,
+}
+
 ///The response type of `get_profile()`
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -13,16 +94,308 @@ pub struct PSNUser {
     pub languages_used: Vec<String>,
     pub plus: u8,
     pub trophy_summary: PSNUserTrophySummary,
+    /// All sizes of avatar. `avatar_url` is the largest one.
+    #[serde(default)]
+    pub avatar_urls: Vec<Avatar>,
+    #[serde(default)]
+    pub is_officially_verified: bool,
+    pub relation: Option<Relation>,
+    pub request_message_flag: Option<bool>,
+    /// Only present when the user shares the real name with you.
+    pub personal_detail: Option<PersonalDetail>,
+    /// One presence for every platform the user is on. The first one is the primary presence.
+    #[serde(default)]
+    pub presences: Vec<Presence>,
+}
+
+impl PSNUser {
+    pub fn primary_presence(&self) -> Option<&Presence> {
+        self.presences.first()
+    }
+}
+
+///The response type of `get_friends()` and `get_friend_requests()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Friends {
+    pub profiles: Vec<Friend>,
+    pub start: u32,
+    pub size: u32,
+    pub total_results: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Friend {
+    pub online_id: String,
+    pub account_id: Option<String>,
+    pub relation: Option<Relation>,
+    #[serde(default)]
+    pub avatar_urls: Vec<Avatar>,
+    pub plus: Option<u8>,
+    #[serde(default)]
+    pub is_officially_verified: bool,
+    pub personal_detail: Option<PersonalDetail>,
+    pub primary_online_status: Option<OnlineStatus>,
+    /// Empty when `FriendQuery::presence` is turned off.
+    #[serde(default)]
+    pub presences: Vec<Presence>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalDetail {
+    pub first_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub last_name: Option<String>,
+    #[serde(default)]
+    pub profile_picture_urls: Vec<Avatar>,
+}
+
+///The response type of `get_presence()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserPresence {
+    pub presence: Presence,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Presence {
+    pub online_status: OnlineStatus,
+    /// `PS4`, `PS3`, `PSVITA` or `PS5`.
+    pub platform: Option<String>,
+    pub np_title_id: Option<String>,
+    pub title_name: Option<String>,
+    /// In game status text set by the title.
+    pub game_status: Option<String>,
+    pub last_online_date: Option<String>,
+}
+
+impl Presence {
+    pub fn is_online(&self) -> bool {
+        self.online_status == OnlineStatus::Online
+    }
+}
+
+string_enum! {
+    /// Unknown online status from PSN is kept as raw string.
+    pub enum OnlineStatus {
+        Online => "online",
+        Offline => "offline",
+        Standby => "standby",
+    }
+}
+
+string_enum! {
+    /// The relation between the logged in account and the user. Unknown relation from PSN is kept as raw string.
+    pub enum Relation {
+        Me => "me",
+        Friend => "friend",
+        FriendOfFriends => "friend of friends",
+        NoRelationship => "no relationship",
+        /// The logged in account sent a friend request to the user.
+        Requesting => "requesting",
+        /// The user sent a friend request to the logged in account.
+        Requested => "requested",
+        Blocked => "blocked",
+    }
+}
+
+///The response type of `get_account_id()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileIds {
+    pub profile: AccountIds,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountIds {
+    pub online_id: String,
+    pub account_id: String,
+}
+
+///The response type of `get_profile_by_account_id()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PSNUserV2 {
+    pub online_id: String,
+    pub about_me: Option<String>,
+    pub avatars: Vec<Avatar>,
+    pub languages: Vec<String>,
+    pub is_plus: bool,
+    pub is_officially_verified: bool,
+    pub is_me: bool,
+}
+
+/// `size` is `s`, `m`, `l` or `xl`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Avatar {
+    pub size: String,
+    #[serde(alias = "avatarUrl", alias = "profilePictureUrl")]
+    pub url: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PSNUserTrophySummary {
-    pub level: u8,
+    pub level: u16,
+    /// progress to next level in percentage.
     pub progress: u8,
     pub earned_trophies: EarnedTrophies,
 }
 
+impl PSNUserTrophySummary {
+    /// The level tier(1 to 10) shown as the trophy level icon.
+    /// Tiers are 1-99, 100-199, ..., 800-998 and the max level 999 is tier 10.
+    pub fn tier(&self) -> u8 {
+        if self.level >= 999 {
+            10
+        } else {
+            (self.level / 100 + 1).min(9) as u8
+        }
+    }
+}
+
+///The response type of `get_activity_feed()` and `get_news_feed()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityFeed {
+    #[serde(default)]
+    pub feed: Vec<Story>,
+    /// `None` when it's the last page.
+    pub next_page: Option<u32>,
+}
+
+/// Story details are in `source` and `targets`. Use the helper methods for the common ones.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Story {
+    pub story_id: String,
+    pub story_type: StoryType,
+    pub date: String,
+    pub caption: Option<String>,
+    pub source: Option<StoryEntity>,
+    #[serde(default)]
+    pub targets: Vec<StoryEntity>,
+    /// Stories of the same type grouped into this one. e.g. multiple trophies earned in a row.
+    #[serde(default)]
+    pub condensed_stories: Vec<Story>,
+    pub thumbnail_image_url: Option<String>,
+    pub large_image_url: Option<String>,
+    #[serde(default)]
+    pub like_count: u32,
+    #[serde(default)]
+    pub comment_count: u32,
+}
+
+/// `type` is the kind of `meta`. e.g. `ONLINE_ID`, `TITLE_ID`, `LONG_TITLE_NAME` or `TROPHY_NAME`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryEntity {
+    pub meta: String,
+    #[serde(rename = "type")]
+    pub entity_type: String,
+}
+
+impl Story {
+    /// Find the meta of `entity_type` in `source` and `targets`.
+    pub fn meta(&self, entity_type: &str) -> Option<&str> {
+        self.source
+            .iter()
+            .chain(self.targets.iter())
+            .find(|e| e.entity_type == entity_type)
+            .map(|e| e.meta.as_str())
+    }
+
+    /// online id of the user the story belongs to.
+    pub fn online_id(&self) -> Option<&str> {
+        self.meta("ONLINE_ID")
+    }
+
+    pub fn title_id(&self) -> Option<&str> {
+        self.meta("TITLE_ID")
+    }
+
+    pub fn title_name(&self) -> Option<&str> {
+        self.meta("LONG_TITLE_NAME")
+            .or_else(|| self.meta("TITLE_NAME"))
+    }
+
+    /// Only for `StoryType::TrophyEarned`.
+    pub fn trophy_name(&self) -> Option<&str> {
+        self.meta("TROPHY_NAME")
+    }
+
+    /// Only for `StoryType::TrophyEarned`.
+    pub fn trophy_detail(&self) -> Option<&str> {
+        self.meta("TROPHY_DETAIL")
+    }
+
+    /// Only for `StoryType::Broadcast`.
+    pub fn broadcast_url(&self) -> Option<&str> {
+        self.meta("BROADCAST_URL")
+    }
+}
+
+string_enum! {
+    /// Unknown story type from PSN is kept as raw string.
+    pub enum StoryType {
+        TrophyEarned => "TROPHY",
+        GamePlayed => "PLAYED_GAME",
+        Broadcast => "BROADCASTING",
+        SharedScreenshot => "SCREENSHOT_UPLOAD" | "SCREENSHOT",
+    }
+}
+
+///The response type of `get_privacy_settings()` and the body of `update_privacy_settings()`
+/// Keys are the setting names from PSN. e.g. `onlineStatus`, `friendList`, `trophies` or `activities`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacySettings {
+    pub privacy_settings: HashMap<String, PrivacyLevel>,
+}
+
+impl PrivacySettings {
+    pub fn get(&self, setting: &str) -> Option<&PrivacyLevel> {
+        self.privacy_settings.get(setting)
+    }
+
+    pub fn set(&mut self, setting: impl Into<String>, level: PrivacyLevel) -> &mut Self {
+        self.privacy_settings.insert(setting.into(), level);
+        self
+    }
+}
+
+string_enum! {
+    /// Who can see a privacy setting. Unknown level from PSN is kept as raw string.
+    pub enum PrivacyLevel {
+        Anyone => "anyone",
+        FriendsOfFriends => "friendsOfFriends",
+        Friends => "friends",
+        NoOne => "noOne",
+    }
+}
+
+///The response type of `get_block_list()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockList {
+    #[serde(default)]
+    pub block_list: Vec<BlockedUser>,
+    pub start: u32,
+    pub size: u32,
+    pub total_results: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedUser {
+    pub online_id: String,
+}
+
 ///The response type of `get_titles()`
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -32,6 +405,38 @@ pub struct TrophyTitles {
     pub trophy_titles: Vec<TrophyTitle>,
 }
 
+impl TrophyTitles {
+    /// PSN returns titles ordered by last update date and it has no sort option so this sorts the titles of current page locally.
+    /// Use `PSN::get_all_titles` with `TitleQuery::sort` to sort all titles of a user.
+    pub fn sort(&mut self, sort: TitleSort) {
+        sort.apply(&mut self.trophy_titles);
+    }
+}
+
+/// `LastUpdateDate` and `Progress` are in descending order. `Name` is in ascending order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleSort {
+    LastUpdateDate,
+    Name,
+    Progress,
+}
+
+impl TitleSort {
+    pub fn apply(self, titles: &mut [TrophyTitle]) {
+        match self {
+            TitleSort::LastUpdateDate => titles.sort_by(|a, b| {
+                b.title_detail
+                    .last_update_date
+                    .cmp(&a.title_detail.last_update_date)
+            }),
+            TitleSort::Name => titles.sort_by(|a, b| a.trophy_title_name.cmp(&b.trophy_title_name)),
+            TitleSort::Progress => {
+                titles.sort_by_key(|t| std::cmp::Reverse(t.title_detail.progress))
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TrophyTitle {
@@ -44,6 +449,8 @@ pub struct TrophyTitle {
     pub defined_trophies: EarnedTrophies,
     #[serde(alias = "comparedUser")]
     pub title_detail: TitleDetail,
+    /// The account you use to login PSN. `None` when it's the same user as `title_detail`.
+    pub from_user: Option<TitleDetail>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -54,7 +461,7 @@ pub struct TitleDetail {
     pub last_update_date: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct EarnedTrophies {
     pub platinum: u32,
     pub gold: u32,
@@ -62,6 +469,19 @@ pub struct EarnedTrophies {
     pub bronze: u32,
 }
 
+impl EarnedTrophies {
+    pub fn total(&self) -> u32 {
+        self.platinum + self.gold + self.silver + self.bronze
+    }
+
+    fn add(&mut self, other: &EarnedTrophies) {
+        self.platinum += other.platinum;
+        self.gold += other.gold;
+        self.silver += other.silver;
+        self.bronze += other.bronze;
+    }
+}
+
 ///The response type of `get_trophy_set()`
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -69,21 +489,242 @@ pub struct TrophySet {
     pub trophies: Vec<Trophy>,
 }
 
+impl TrophySet {
+    /// PSN doesn't include group id in trophy list response so use this to mark the trophies fetched from `get_trophy_group_set()`.
+    pub fn with_group_id(mut self, group_id: &str) -> Self {
+        for trophy in self.trophies.iter_mut() {
+            trophy.trophy_group_id = Some(group_id.to_owned());
+        }
+        self
+    }
+}
+
+///The response type of `get_trophy_groups()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyGroups {
+    pub trophy_title_name: String,
+    pub trophy_title_detail: String,
+    pub trophy_title_icon_url: String,
+    pub trophy_title_platfrom: String,
+    pub defined_trophies: EarnedTrophies,
+    pub trophy_groups: Vec<TrophyGroup>,
+}
+
+/// `trophy_group_id` is `default` for base game and `001`, `002`... for DLC packs.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyGroup {
+    pub trophy_group_id: String,
+    pub trophy_group_name: String,
+    pub trophy_group_detail: Option<String>,
+    pub trophy_group_icon_url: String,
+    pub trophy_group_small_icon_url: Option<String>,
+    pub defined_trophies: EarnedTrophies,
+    #[serde(alias = "comparedUser")]
+    pub group_detail: Option<TrophyGroupDetail>,
+}
+
+impl TrophyGroup {
+    pub fn is_base_game(&self) -> bool {
+        self.trophy_group_id == "default"
+    }
+}
+
+/// `last_update_date` field will return `None` if no trophy of this group has been earned.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyGroupDetail {
+    pub progress: u8,
+    pub earned_trophies: EarnedTrophies,
+    pub last_update_date: Option<String>,
+}
+
 /// If one trophy is hidden and the account you use to login PSN has not obtained it,
 /// all the `Option<String>` fields will return `None`.
+///
+/// `trophy_group_id` is `None` unless the set is marked with `TrophySet::with_group_id`.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Trophy {
     pub trophy_id: u8,
+    pub trophy_group_id: Option<String>,
     pub trophy_hidden: bool,
-    pub trophy_type: Option<String>,
+    pub trophy_type: Option<TrophyType>,
     pub trophy_name: Option<String>,
     pub trophy_detail: Option<String>,
     pub trophy_icon_url: Option<String>,
-    pub trophy_rare: u8,
-    pub trophy_earned_rate: String,
+    pub trophy_rare: Rarity,
+    /// `None` when PSN returns an empty or malformed earned rate.
+    #[serde(default, deserialize_with = "de_earned_rate")]
+    pub trophy_earned_rate: Option<f32>,
     #[serde(alias = "comparedUser")]
     pub user_info: TrophyUser,
+    /// The account you use to login PSN. `None` when it's the same user as `user_info`.
+    pub from_user: Option<TrophyUser>,
+}
+
+///The response type of `PSN::compare_trophies()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyComparison {
+    pub np_communication_id: String,
+    pub trophies: Vec<TrophyCompared>,
+}
+
+/// `user_a` or `user_b` is `None` when the trophy is missing from that user's trophy set.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyCompared {
+    pub trophy_id: u8,
+    pub trophy_hidden: bool,
+    pub trophy_type: Option<TrophyType>,
+    pub trophy_name: Option<String>,
+    pub trophy_detail: Option<String>,
+    pub trophy_icon_url: Option<String>,
+    pub trophy_rare: Rarity,
+    pub trophy_earned_rate: Option<f32>,
+    pub user_a: Option<TrophyUser>,
+    pub user_b: Option<TrophyUser>,
+}
+
+impl TrophyComparison {
+    /// join two trophy sets of the same title by trophy id.
+    pub fn new(np_communication_id: &str, set_a: TrophySet, set_b: TrophySet) -> Self {
+        let mut users_b = set_b
+            .trophies
+            .into_iter()
+            .map(|t| (t.trophy_id, t))
+            .collect::<std::collections::BTreeMap<_, _>>();
+
+        let mut trophies = Vec::with_capacity(set_a.trophies.len());
+
+        for a in set_a.trophies.into_iter() {
+            let b = users_b.remove(&a.trophy_id);
+            // prefer user b's trophy info when user a has hidden info.
+            let (hidden, info) = match b {
+                Some(ref b) if a.trophy_name.is_none() => (b.trophy_hidden, b),
+                _ => (a.trophy_hidden, &a),
+            };
+            let compared = TrophyCompared {
+                trophy_id: a.trophy_id,
+                trophy_hidden: hidden,
+                trophy_type: info.trophy_type.clone(),
+                trophy_name: info.trophy_name.clone(),
+                trophy_detail: info.trophy_detail.clone(),
+                trophy_icon_url: info.trophy_icon_url.clone(),
+                trophy_rare: info.trophy_rare,
+                trophy_earned_rate: info.trophy_earned_rate,
+                user_a: None,
+                user_b: None,
+            };
+            trophies.push(TrophyCompared {
+                user_a: Some(a.user_info),
+                user_b: b.map(|b| b.user_info),
+                ..compared
+            });
+        }
+
+        for (_, b) in users_b.into_iter() {
+            trophies.push(TrophyCompared {
+                trophy_id: b.trophy_id,
+                trophy_hidden: b.trophy_hidden,
+                trophy_type: b.trophy_type,
+                trophy_name: b.trophy_name,
+                trophy_detail: b.trophy_detail,
+                trophy_icon_url: b.trophy_icon_url,
+                trophy_rare: b.trophy_rare,
+                trophy_earned_rate: b.trophy_earned_rate,
+                user_a: None,
+                user_b: Some(b.user_info),
+            });
+        }
+
+        TrophyComparison {
+            np_communication_id: np_communication_id.to_owned(),
+            trophies,
+        }
+    }
+}
+
+///The response type of `PSN::compare_titles()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleComparison {
+    pub online_id_a: String,
+    pub online_id_b: String,
+    pub titles: Vec<TitleCompared>,
+}
+
+/// `user_a` or `user_b` is `None` when that user has not played the title.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleCompared {
+    pub np_communication_id: String,
+    pub trophy_title_name: String,
+    pub trophy_title_icon_url: String,
+    pub trophy_title_platform: String,
+    pub defined_trophies: EarnedTrophies,
+    pub user_a: Option<TitleDetail>,
+    pub user_b: Option<TitleDetail>,
+}
+
+impl TitleComparison {
+    /// join two users' trophy titles by np communication id. Titles are in user a's order followed by titles only user b has.
+    pub fn new(
+        online_id_a: &str,
+        titles_a: Vec<TrophyTitle>,
+        online_id_b: &str,
+        titles_b: Vec<TrophyTitle>,
+    ) -> Self {
+        let mut details_b = HashMap::with_capacity(titles_b.len());
+        let mut only_b = Vec::new();
+        let ids_a = titles_a
+            .iter()
+            .map(|t| t.np_communication_id.as_str())
+            .collect::<std::collections::HashSet<_>>();
+
+        for b in titles_b.into_iter() {
+            if ids_a.contains(b.np_communication_id.as_str()) {
+                details_b.insert(b.np_communication_id, b.title_detail);
+            } else {
+                only_b.push(b);
+            }
+        }
+
+        let mut titles = Vec::with_capacity(titles_a.len() + only_b.len());
+
+        for a in titles_a.into_iter() {
+            let user_b = details_b.remove(&a.np_communication_id);
+            titles.push(TitleCompared {
+                np_communication_id: a.np_communication_id,
+                trophy_title_name: a.trophy_title_name,
+                trophy_title_icon_url: a.trophy_title_icon_url,
+                trophy_title_platform: a.trophy_title_platfrom,
+                defined_trophies: a.defined_trophies,
+                user_a: Some(a.title_detail),
+                user_b,
+            });
+        }
+
+        for b in only_b.into_iter() {
+            titles.push(TitleCompared {
+                np_communication_id: b.np_communication_id,
+                trophy_title_name: b.trophy_title_name,
+                trophy_title_icon_url: b.trophy_title_icon_url,
+                trophy_title_platform: b.trophy_title_platfrom,
+                defined_trophies: b.defined_trophies,
+                user_a: None,
+                user_b: Some(b.title_detail),
+            });
+        }
+
+        TitleComparison {
+            online_id_a: online_id_a.to_owned(),
+            online_id_b: online_id_b.to_owned(),
+            titles,
+        }
+    }
 }
 
 /// `earned_date` field will return `None` if this has not been earned by according `online_id`.
@@ -95,6 +736,385 @@ pub struct TrophyUser {
     pub earned_date: Option<String>,
 }
 
+/// Parse a PSN date(UTC ISO 8601 string). Return `None` when it's not a valid date.
+#[cfg(feature = "chrono")]
+pub fn parse_date(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    date.parse().ok()
+}
+
+#[cfg(feature = "chrono")]
+trait DateField {
+    fn to_date(&self) -> Option<chrono::DateTime<chrono::Utc>>;
+}
+
+#[cfg(feature = "chrono")]
+impl DateField for String {
+    fn to_date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        parse_date(self)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl DateField for Option<String> {
+    fn to_date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.as_deref().and_then(parse_date)
+    }
+}
+
+// date fields are kept as raw strings so a malformed date doesn't fail the whole response.
+// the accessors parse them with `chrono` feature enabled and return `None` when a date is missing or malformed.
+macro_rules! date_accessors {
+    ($($ty:ident { $($getter:ident => $field:ident),+ })+) => {
+        $(
+            #[cfg(feature = "chrono")]
+            impl $ty {
+                $(
+                    pub fn $getter(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+                        DateField::to_date(&self.$field)
+                    }
+                )+
+            }
+        )+
+    };
+}
+
+date_accessors! {
+    Presence { last_online_at => last_online_date }
+    Story { posted_at => date }
+    TitleDetail { last_update_at => last_update_date }
+    TrophyGroupDetail { last_update_at => last_update_date }
+    TrophyUser { earned_at => earned_date }
+    TitleReport { last_update_at => last_update_date }
+    EarnedTrophy { earned_at => earned_date }
+    TrophyTitleV2 { last_updated_at => last_updated_date_time }
+    UserTrophyGroupsV2 { last_updated_at => last_updated_date_time }
+    UserTrophyGroupV2 { last_updated_at => last_updated_date_time }
+    TrophyV2 { earned_at => earned_date_time }
+    MessageThreadNew { thread_modified_at => thread_modified_date }
+    MessageThreadResponse { thread_modified_at => thread_modified_date }
+    MessageThreadSummary { thread_modified_at => thread_modified_date }
+    MessageThread { thread_modified_at => thread_modified_date }
+    ThreadProperty { thread_join_at => thread_join_date }
+    MessageEventDetail { post_at => post_date }
+}
+
+string_enum! {
+    /// Unknown trophy type from PSN is kept as raw string.
+    pub enum TrophyType {
+        Bronze => "bronze",
+        Silver => "silver",
+        Gold => "gold",
+        Platinum => "platinum",
+    }
+}
+
+/// The `trophyRare` value from PSN. Unknown value is kept as raw number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rarity {
+    UltraRare,
+    VeryRare,
+    Rare,
+    Common,
+    Unknown(u8),
+}
+
+impl From<u8> for Rarity {
+    fn from(rare: u8) -> Self {
+        match rare {
+            0 => Rarity::UltraRare,
+            1 => Rarity::VeryRare,
+            2 => Rarity::Rare,
+            3 => Rarity::Common,
+            _ => Rarity::Unknown(rare),
+        }
+    }
+}
+
+impl From<Rarity> for u8 {
+    fn from(rarity: Rarity) -> u8 {
+        match rarity {
+            Rarity::UltraRare => 0,
+            Rarity::VeryRare => 1,
+            Rarity::Rare => 2,
+            Rarity::Common => 3,
+            Rarity::Unknown(rare) => rare,
+        }
+    }
+}
+
+impl Serialize for Rarity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8((*self).into())
+    }
+}
+
+impl<'de> Deserialize<'de> for Rarity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u8::deserialize(deserializer).map(Into::into)
+    }
+}
+
+// PSN returns earned rate as string like "12.3" and it's parsed to f32.
+// an empty or malformed rate is `None` so it doesn't fail the whole response.
+fn de_earned_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Rate {
+        Str(String),
+        Num(f32),
+        Other(serde::de::IgnoredAny),
+    }
+
+    Ok(match Rate::deserialize(deserializer)? {
+        Rate::Str(s) => s.trim().parse().ok(),
+        Rate::Num(n) => Some(n),
+        Rate::Other(_) => None,
+    })
+}
+
+///The response type of `PSN::trophy_report()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyReport {
+    pub online_id: String,
+    pub earned_trophies: EarnedTrophies,
+    pub defined_trophies: EarnedTrophies,
+    pub titles: Vec<TitleReport>,
+    /// earned platinum trophies ordered by earned date.
+    pub platinums: Vec<EarnedTrophy>,
+    /// earned trophies with the lowest earned rate. At most `TrophyReport::RAREST_COUNT` trophies and the ones without earned rate are left out.
+    pub rarest: Vec<EarnedTrophy>,
+    /// all earned trophies ordered by earned date.
+    pub timeline: Vec<EarnedTrophy>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleReport {
+    pub np_communication_id: String,
+    pub trophy_title_name: String,
+    pub trophy_title_platform: String,
+    pub progress: u8,
+    pub earned_trophies: EarnedTrophies,
+    pub defined_trophies: EarnedTrophies,
+    pub last_update_date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EarnedTrophy {
+    pub np_communication_id: String,
+    pub trophy_title_name: String,
+    pub trophy_id: u8,
+    pub trophy_type: Option<TrophyType>,
+    pub trophy_name: Option<String>,
+    pub trophy_rare: Rarity,
+    pub trophy_earned_rate: Option<f32>,
+    pub earned_date: String,
+}
+
+impl TrophyReport {
+    pub const RAREST_COUNT: usize = 10;
+
+    /// aggregate trophy titles and their trophy sets(compared against `online_id`) into a report.
+    pub fn new(online_id: &str, titles: Vec<(TrophyTitle, TrophySet)>) -> Self {
+        let mut earned_trophies = EarnedTrophies::default();
+        let mut defined_trophies = EarnedTrophies::default();
+        let mut reports = Vec::with_capacity(titles.len());
+        let mut timeline = Vec::new();
+
+        for (title, set) in titles.into_iter() {
+            earned_trophies.add(&title.title_detail.earned_trophies);
+            defined_trophies.add(&title.defined_trophies);
+
+            for trophy in set.trophies.into_iter() {
+                if let (true, Some(earned_date)) =
+                    (trophy.user_info.earned, trophy.user_info.earned_date)
+                {
+                    timeline.push(EarnedTrophy {
+                        np_communication_id: title.np_communication_id.clone(),
+                        trophy_title_name: title.trophy_title_name.clone(),
+                        trophy_id: trophy.trophy_id,
+                        trophy_type: trophy.trophy_type,
+                        trophy_name: trophy.trophy_name,
+                        trophy_rare: trophy.trophy_rare,
+                        trophy_earned_rate: trophy.trophy_earned_rate,
+                        earned_date,
+                    });
+                }
+            }
+
+            reports.push(TitleReport {
+                np_communication_id: title.np_communication_id,
+                trophy_title_name: title.trophy_title_name,
+                trophy_title_platform: title.trophy_title_platfrom,
+                progress: title.title_detail.progress,
+                earned_trophies: title.title_detail.earned_trophies,
+                defined_trophies: title.defined_trophies,
+                last_update_date: title.title_detail.last_update_date,
+            });
+        }
+
+        // PSN dates are UTC ISO 8601 strings so they can be ordered as strings.
+        timeline.sort_by(|a, b| {
+            a.earned_date
+                .cmp(&b.earned_date)
+                .then(a.trophy_id.cmp(&b.trophy_id))
+        });
+
+        let platinums = timeline
+            .iter()
+            .filter(|t| t.trophy_type == Some(TrophyType::Platinum))
+            .cloned()
+            .collect();
+
+        let mut rarest = timeline
+            .iter()
+            .filter(|t| t.trophy_earned_rate.is_some())
+            .cloned()
+            .collect::<Vec<_>>();
+        rarest.sort_by(|a, b| {
+            a.trophy_earned_rate
+                .partial_cmp(&b.trophy_earned_rate)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        rarest.truncate(Self::RAREST_COUNT);
+
+        TrophyReport {
+            online_id: online_id.to_owned(),
+            earned_trophies,
+            defined_trophies,
+            titles: reports,
+            platinums,
+            rarest,
+            timeline,
+        }
+    }
+}
+
+///The response type of `get_titles_v2()`
+///
+/// The newer trophy api includes PS5 titles and it's keyed by account id.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyTitlesV2 {
+    pub trophy_titles: Vec<TrophyTitleV2>,
+    pub total_item_count: u32,
+    pub next_offset: Option<u32>,
+    pub previous_offset: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyTitleV2 {
+    pub np_service_name: NpServiceName,
+    pub np_communication_id: String,
+    pub trophy_set_version: String,
+    pub trophy_title_name: String,
+    pub trophy_title_detail: Option<String>,
+    pub trophy_title_icon_url: String,
+    /// comma separated platforms like `PS5` or `PS4,PSVITA`.
+    pub trophy_title_platform: String,
+    pub has_trophy_groups: bool,
+    pub defined_trophies: EarnedTrophies,
+    pub progress: u8,
+    pub earned_trophies: EarnedTrophies,
+    pub hidden_flag: bool,
+    pub last_updated_date_time: String,
+}
+
+/// `Trophy` is for PS3, PSVITA and PS4 titles. `Trophy2` is for PS5 titles.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NpServiceName {
+    Trophy,
+    Trophy2,
+}
+
+impl NpServiceName {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NpServiceName::Trophy => "trophy",
+            NpServiceName::Trophy2 => "trophy2",
+        }
+    }
+}
+
+///The response type of `get_trophy_groups_v2()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyGroupsV2 {
+    pub trophy_set_version: String,
+    pub trophy_title_name: String,
+    pub trophy_title_icon_url: String,
+    pub trophy_title_platform: String,
+    pub defined_trophies: EarnedTrophies,
+    pub trophy_groups: Vec<TrophyGroupV2>,
+}
+
+/// `trophy_group_id` is `default` for base game and `001`, `002`... for DLC packs.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyGroupV2 {
+    pub trophy_group_id: String,
+    pub trophy_group_name: String,
+    pub trophy_group_detail: Option<String>,
+    pub trophy_group_icon_url: String,
+    pub defined_trophies: EarnedTrophies,
+}
+
+///The response type of `get_user_trophy_groups_v2()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserTrophyGroupsV2 {
+    pub trophy_set_version: String,
+    pub hidden_flag: bool,
+    pub progress: u8,
+    pub earned_trophies: EarnedTrophies,
+    pub last_updated_date_time: String,
+    pub trophy_groups: Vec<UserTrophyGroupV2>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserTrophyGroupV2 {
+    pub trophy_group_id: String,
+    pub progress: u8,
+    pub earned_trophies: EarnedTrophies,
+    pub last_updated_date_time: Option<String>,
+}
+
+///The response type of `get_trophies_v2()` and `get_user_trophies_v2()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophiesV2 {
+    pub trophy_set_version: String,
+    pub has_trophy_groups: bool,
+    pub trophies: Vec<TrophyV2>,
+    pub total_item_count: u32,
+    pub next_offset: Option<u32>,
+    pub previous_offset: Option<u32>,
+}
+
+/// `get_trophies_v2()` returns the trophy definition fields and `get_user_trophies_v2()` returns the user fields(`earned`, `earned_date_time`, `trophy_rare` and `trophy_earned_rate`).
+/// The fields not included in the response are `None`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyV2 {
+    pub trophy_id: u32,
+    pub trophy_hidden: bool,
+    pub trophy_type: TrophyType,
+    pub trophy_group_id: Option<String>,
+    pub trophy_name: Option<String>,
+    pub trophy_detail: Option<String>,
+    pub trophy_icon_url: Option<String>,
+    pub earned: Option<bool>,
+    pub earned_date_time: Option<String>,
+    pub trophy_rare: Option<Rarity>,
+    #[serde(default, deserialize_with = "de_earned_rate")]
+    pub trophy_earned_rate: Option<f32>,
+}
+
 ///The response type of `generate_message_thread()`
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -131,6 +1151,13 @@ pub struct MessageThreadSummary {
     pub thread_modified_date: String,
 }
 
+impl MessageThreadSummary {
+    /// `thread_type` is 0 for 1:1 threads and 1 for group threads.
+    pub fn is_one_on_one(&self) -> bool {
+        self.thread_type == 0
+    }
+}
+
 ///The response type of `get_message_thread()`
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -151,6 +1178,13 @@ pub struct MessageThread {
     pub end_of_thread_event: bool,
 }
 
+///The response type of `get_message_thread_members()`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageThreadMembers {
+    pub thread_members: Vec<ThreadMember>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ThreadMember {
@@ -209,17 +1243,179 @@ pub struct ThreadEvent {
 pub struct MessageEventDetail {
     pub event_index: String,
     pub post_date: String,
-    pub event_category_code: u32,
+    pub event_category_code: MessageEventKind,
     pub alt_event_category_code: u32,
     pub sender: ThreadMember,
     pub attached_media_path: Option<String>,
     pub message_detail: MessageDetail,
+    /// The members joined or left for `MessageEventKind::MemberJoined` and `MessageEventKind::MemberLeft`.
+    pub user_action_detail: Option<UserActionDetail>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl MessageEventDetail {
+    /// The content key of the attachment for `PSNRequest::download_attachment`. `None` when the event has no attachment.
+    pub fn attachment_content_key(&self) -> Option<&str> {
+        let path = self.attached_media_path.as_deref()?;
+
+        Some(
+            path.split(&['?', '&'][..])
+                .find_map(|query| query.strip_prefix("contentKey="))
+                .unwrap_or("image-data-0"),
+        )
+    }
+
+    /// Decode the payload of the event by its kind.
+    pub fn content(&self) -> MessageContent<'_> {
+        let members = || {
+            self.user_action_detail
+                .as_ref()
+                .map(|d| d.target_users.as_slice())
+                .unwrap_or(&[])
+        };
+
+        match self.event_category_code {
+            MessageEventKind::Text => {
+                MessageContent::Text(self.message_detail.body.as_deref().unwrap_or(""))
+            }
+            MessageEventKind::Image => MessageContent::Image {
+                attached_media_path: self.attached_media_path.as_deref(),
+                text: self.message_detail.body.as_deref(),
+            },
+            MessageEventKind::Sticker => match self.message_detail.sticker_detail.as_ref() {
+                Some(sticker) => MessageContent::Sticker(sticker),
+                None => MessageContent::Other,
+            },
+            MessageEventKind::Voice => MessageContent::Voice {
+                attached_media_path: self.attached_media_path.as_deref(),
+                play_time: self
+                    .message_detail
+                    .voice_detail
+                    .as_ref()
+                    .and_then(|v| v.play_time),
+            },
+            MessageEventKind::MemberJoined => MessageContent::MemberJoined(members()),
+            MessageEventKind::MemberLeft => MessageContent::MemberLeft(members()),
+            MessageEventKind::System(_) | MessageEventKind::Unknown(_) => MessageContent::Other,
+        }
+    }
+}
+
+/// The payload of a message event. See `MessageEventDetail::content`.
+#[derive(Debug)]
+pub enum MessageContent<'a> {
+    Text(&'a str),
+    /// Use `PSN::download_attachment` to get the image.
+    Image {
+        attached_media_path: Option<&'a str>,
+        text: Option<&'a str>,
+    },
+    Sticker(&'a StickerDetail),
+    /// Use `PSN::download_attachment` to get the voice data.
+    Voice {
+        attached_media_path: Option<&'a str>,
+        play_time: Option<u32>,
+    },
+    MemberJoined(&'a [TargetUser]),
+    MemberLeft(&'a [TargetUser]),
+    /// System events and unknown events.
+    Other,
+}
+
+/// The `eventCategoryCode` of a message event. Unknown code from PSN is kept as raw number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageEventKind {
+    Text,
+    Image,
+    Voice,
+    Sticker,
+    MemberJoined,
+    MemberLeft,
+    /// Other system events like thread renamed.
+    System(u32),
+    Unknown(u32),
+}
+
+impl MessageEventKind {
+    pub fn code(&self) -> u32 {
+        match self {
+            MessageEventKind::Text => 1,
+            MessageEventKind::Image => 3,
+            MessageEventKind::Voice => 1011,
+            MessageEventKind::Sticker => 1013,
+            MessageEventKind::MemberJoined => 2001,
+            MessageEventKind::MemberLeft => 2002,
+            MessageEventKind::System(code) | MessageEventKind::Unknown(code) => *code,
+        }
+    }
+}
+
+impl From<u32> for MessageEventKind {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => MessageEventKind::Text,
+            3 => MessageEventKind::Image,
+            1011 => MessageEventKind::Voice,
+            1013 => MessageEventKind::Sticker,
+            2001 => MessageEventKind::MemberJoined,
+            2002 => MessageEventKind::MemberLeft,
+            2000..=2999 => MessageEventKind::System(code),
+            _ => MessageEventKind::Unknown(code),
+        }
+    }
+}
+
+impl Serialize for MessageEventKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageEventKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u32::deserialize(deserializer).map(Into::into)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct MessageDetail {
     pub body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticker_detail: Option<StickerDetail>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_detail: Option<VoiceDetail>,
+}
+
+/// A sticker from a sticker package. The same detail received from a message can be used to send the sticker.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StickerDetail {
+    pub manifest_file_url: String,
+    pub package_id: String,
+    pub number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+}
+
+/// `play_time` is in seconds.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceDetail {
+    pub play_time: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserActionDetail {
+    #[serde(default)]
+    pub target_users: Vec<TargetUser>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetUser {
+    pub online_id: String,
+    pub account_id: Option<String>,
 }
 
 ///The response type of `search_store_items()`
@@ -516,3 +1712,30 @@ pub struct StoreSearchRelationshipData {
     #[serde(alias = "type")]
     pub typ: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trophy_tier() {
+        let tier = |level| {
+            PSNUserTrophySummary {
+                level,
+                progress: 0,
+                earned_trophies: EarnedTrophies::default(),
+            }
+            .tier()
+        };
+
+        assert_eq!(tier(1), 1);
+        assert_eq!(tier(99), 1);
+        assert_eq!(tier(100), 2);
+        assert_eq!(tier(199), 2);
+        assert_eq!(tier(200), 3);
+        assert_eq!(tier(800), 9);
+        assert_eq!(tier(950), 9);
+        assert_eq!(tier(998), 9);
+        assert_eq!(tier(999), 10);
+    }
+}
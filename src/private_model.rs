@@ -1,4 +1,6 @@
-use crate::models::MessageDetail;
+use crate::models::{
+    FavoriteDetail, MessageDetail, MessageEventKind, NotificationDetail, StickerDetail,
+};
 
 #[cfg(feature = "default")]
 #[derive(Deserialize, Debug)]
@@ -20,6 +22,13 @@ pub(crate) struct PSNResponseErrorInner {
     pub(crate) message: String,
 }
 
+#[cfg(feature = "default")]
+impl From<PSNResponseError> for crate::psn::PSNError {
+    fn from(e: PSNResponseError) -> Self {
+        crate::psn::PSNError::FromPSNResponse(e.error.code, e.error.message.into_boxed_str())
+    }
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct SendMessage {
@@ -29,17 +38,31 @@ pub(crate) struct SendMessage {
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct SendMessageEventDetail {
-    event_category_code: u8,
+    event_category_code: MessageEventKind,
     message_detail: MessageDetail,
 }
 
 impl SendMessage {
-    pub(crate) fn new(body: Option<&str>, event_category_code: u8) -> Self {
+    pub(crate) fn new(body: Option<&str>, event_category_code: MessageEventKind) -> Self {
         SendMessage {
             message_event_detail: SendMessageEventDetail {
                 event_category_code,
                 message_detail: MessageDetail {
                     body: Some(body.unwrap_or("").to_owned()),
+                    ..Default::default()
+                },
+            },
+        }
+    }
+
+    pub(crate) fn sticker(sticker: &StickerDetail) -> Self {
+        SendMessage {
+            message_event_detail: SendMessageEventDetail {
+                event_category_code: MessageEventKind::Sticker,
+                message_detail: MessageDetail {
+                    body: Some("".to_owned()),
+                    sticker_detail: Some(sticker.clone()),
+                    ..Default::default()
                 },
             },
         }
@@ -66,14 +89,109 @@ struct NewThreadMember<'a> {
 
 impl<'a> GenerateNewThread<'a> {
     pub(crate) fn new(other_id: &'a str, self_id: &'a str) -> Self {
+        GenerateNewThread::with_members(&[other_id], self_id)
+    }
+
+    // online ids are case insensitive and each member is only added once.
+    pub(crate) fn with_members(other_ids: &[&'a str], self_id: &'a str) -> Self {
+        let mut thread_members: Vec<NewThreadMember> = Vec::with_capacity(other_ids.len() + 1);
+        for online_id in other_ids.iter().copied().chain(Some(self_id)) {
+            if !thread_members
+                .iter()
+                .any(|m| m.online_id.eq_ignore_ascii_case(online_id))
+            {
+                thread_members.push(NewThreadMember { online_id });
+            }
+        }
+
         GenerateNewThread {
-            thread_detail: NewThreadMembers {
-                thread_members: vec![
-                    NewThreadMember {
-                        online_id: other_id,
-                    },
-                    NewThreadMember { online_id: self_id },
-                ],
+            thread_detail: NewThreadMembers { thread_members },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AddThreadMembers<'a> {
+    user_action_detail: TargetUsers<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TargetUsers<'a> {
+    target_users: Vec<NewThreadMember<'a>>,
+}
+
+impl<'a> AddThreadMembers<'a> {
+    pub(crate) fn new(online_ids: &[&'a str]) -> Self {
+        AddThreadMembers {
+            user_action_detail: TargetUsers {
+                target_users: online_ids
+                    .iter()
+                    .map(|online_id| NewThreadMember { online_id })
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetThreadName<'a> {
+    thread_name_detail: NewThreadName<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct NewThreadName<'a> {
+    thread_name: &'a str,
+}
+
+impl<'a> SetThreadName<'a> {
+    pub(crate) fn new(thread_name: &'a str) -> Self {
+        SetThreadName {
+            thread_name_detail: NewThreadName { thread_name },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetSeenFlag {
+    seen_flag: bool,
+}
+
+impl SetSeenFlag {
+    pub(crate) fn new() -> Self {
+        SetSeenFlag { seen_flag: true }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetFavorite {
+    favorite_detail: FavoriteDetail,
+}
+
+impl SetFavorite {
+    pub(crate) fn new(favorite_flag: bool) -> Self {
+        SetFavorite {
+            favorite_detail: FavoriteDetail { favorite_flag },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetNotification {
+    notification_detail: NotificationDetail,
+}
+
+impl SetNotification {
+    pub(crate) fn new(push_notification_flag: bool) -> Self {
+        SetNotification {
+            notification_detail: NotificationDetail {
+                push_notification_flag,
             },
         }
     }
@@ -5,9 +5,16 @@ use rand::distributions::Alphanumeric;
 use rand::Rng;
 use serde::de::DeserializeOwned;
 
+use crate::attachment::{Attachment, ImageFormat};
 use crate::metas::meta::*;
-use crate::private_model::{GenerateNewThread, SendMessage};
-use crate::types::PSNFuture;
+use crate::models::{
+    MessageEventKind, NpServiceName, PrivacySettings, StickerDetail, TokenValidation,
+};
+use crate::private_model::{
+    AddThreadMembers, GenerateNewThread, SendMessage, SetFavorite, SetNotification, SetSeenFlag,
+    SetThreadName,
+};
+use crate::types::{FriendQuery, FriendRequestType, PSNFuture, TitleQuery};
 
 /// You can override `PSNRequest` trait to impl your preferred http client
 /// The crate can provide the url, body format and some headers needed but the response handling you have to write your own.
@@ -40,6 +47,20 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         client: &'se Self::Client,
     ) -> PSNFuture<'se, Result<(), Self::Error>>;
 
+    /// This method will check local `access_token`, `refresh_token` and `npsso` against PSN oauth server.
+    /// A token rejected by PSN(status 400, 401, 403 or 404) is reported as `None` in `TokenValidation`. Other failed statuses are errors.
+    fn validate_tokens<'se>(
+        &'se self,
+        client: &'se Self::Client,
+    ) -> PSNFuture<'se, Result<TokenValidation, Self::Error>>;
+
+    /// This method will revoke local `access_token` and `refresh_token` on PSN oauth server and clear all local credentials.
+    /// Local credentials are cleared even when PSN failed to revoke the tokens.
+    fn logout<'se>(
+        &'se mut self,
+        client: &'se Self::Client,
+    ) -> PSNFuture<'se, Result<(), Self::Error>>;
+
     /// A generic http get handle function. The return type `T` need to impl `serde::deserialize`.
     fn get_by_url_encode<'se, 'st: 'se, T: DeserializeOwned + 'static>(
         &'se self,
@@ -54,6 +75,22 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         url: &'st str,
     ) -> PSNFuture<'se, Result<(), Self::Error>>;
 
+    /// A generic http put handle function. `body` is sent as application/json and can be empty. return status 2xx as successful response.
+    fn put_by_url_encode<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        url: &'st str,
+        body: Vec<u8>,
+    ) -> PSNFuture<'se, Result<(), Self::Error>>;
+
+    /// A generic http post handle function. `body` is sent as application/json. return status 2xx as successful response.
+    fn post_by_url_encode<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        url: &'st str,
+        body: Vec<u8>,
+    ) -> PSNFuture<'se, Result<(), Self::Error>>;
+
     /// A generic multipart/form-data post handle function.
     /// take in multipart boundary to produce a proper heaader.
     fn post_by_multipart<'se, 'st: 'se, T: DeserializeOwned + 'static>(
@@ -64,6 +101,14 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         body: Vec<u8>,
     ) -> PSNFuture<'se, Result<T, Self::Error>>;
 
+    /// A generic http get handle function for binary content like message attachments. The access_token is needed as bearer token.
+    /// return the `Content-Type` header and the body of status 2xx as successful response.
+    fn get_bytes_by_url_encode<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        url: &'st str,
+    ) -> PSNFuture<'se, Result<Attachment, Self::Error>>;
+
     fn get_profile<'se, 'st: 'se, T: DeserializeOwned + 'static>(
         &'se self,
         client: &'se Self::Client,
@@ -75,6 +120,153 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         })
     }
 
+    /// return the primary presence of `online_id`.
+    fn get_presence<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.presence_encode(online_id);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return one page of the activity feed stories of `online_id`. `page` starts from 0 and the next page is `ActivityFeed::next_page`.
+    fn get_activity_feed<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+        page: u32,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.activity_feed_encode(online_id, page);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return one page of the news feed of the logged in account(activities of friends and followed titles).
+    /// `self_online_id` must be set.
+    fn get_news_feed<'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        page: u32,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.news_feed_encode(page);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return the stable account id of an online id. online id can be changed by user but account id can't.
+    fn get_account_id<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.account_id_encode(online_id);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return profile from the newer profile api by account id. It can be used to resolve account id to current online id.
+    fn get_profile_by_account_id<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        account_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.profile_by_account_id_encode(account_id);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return one page of friends of `online_id`. Use `me` as `online_id` for the logged in account.
+    fn get_friends<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+        query: &'st FriendQuery,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.friends_encode(online_id, query);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return one page of pending friend requests of the logged in account.
+    fn get_friend_requests<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        request_type: FriendRequestType,
+        query: &'st FriendQuery,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.friend_requests_encode(request_type, query);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return the privacy settings of the logged in account.
+    fn get_privacy_settings<'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.privacy_settings_encode();
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// update the privacy settings of the logged in account. Only the settings in `settings` are changed.
+    fn update_privacy_settings<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        settings: &'st PrivacySettings,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.privacy_settings_encode();
+            let body = serde_json::to_vec(settings).unwrap_or_default();
+            self.put_by_url_encode(client, url.as_str(), body).await
+        })
+    }
+
+    /// return one page of users blocked by the logged in account.
+    fn get_block_list<'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        offset: u32,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.block_list_encode(offset);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    fn block_user<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.block_user_encode(online_id);
+            self.post_by_url_encode(client, url.as_str(), Vec::new())
+                .await
+        })
+    }
+
+    fn unblock_user<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.block_user_encode(online_id);
+            self.del_by_url_encode(client, url.as_str()).await
+        })
+    }
+
     /// need a legit `offset`(offset can't be larger than the total trophy lists a user have).
     fn get_titles<'se, 'st: 'se, T: DeserializeOwned + 'static>(
         &'se self,
@@ -88,6 +280,19 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         })
     }
 
+    /// The same as `get_titles` with platform filter, icon size, language and limit from `TitleQuery`.
+    fn get_titles_with_query<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+        query: &'st TitleQuery,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.trophy_summary_query_encode(online_id, query);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
     fn get_trophy_set<'se, 'st: 'se, T: DeserializeOwned + 'static>(
         &'se self,
         client: &'se Self::Client,
@@ -100,6 +305,109 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         })
     }
 
+    /// return all trophy groups(base game and DLC packs) of a title.
+    fn get_trophy_groups<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+        np_communication_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.trophy_groups_encode(online_id, np_communication_id);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return trophies of one trophy group. `group_id` is `TrophyGroup::trophy_group_id`.
+    fn get_trophy_group_set<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_id: &'st str,
+        np_communication_id: &'st str,
+        group_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.trophy_group_set_encode(online_id, np_communication_id, group_id);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return trophy titles of PS3, PSVITA, PS4 and PS5 from the newer trophy api.
+    /// `account_id` can be `me` for the account you used to login PSN network.
+    fn get_titles_v2<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        account_id: &'st str,
+        offset: u32,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.trophy_titles_v2_encode(account_id, offset);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return trophy groups defined by a title. `np_service_name` is `TrophyTitleV2::np_service_name`.
+    fn get_trophy_groups_v2<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        np_communication_id: &'st str,
+        np_service_name: NpServiceName,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.trophy_groups_v2_encode(np_communication_id, np_service_name);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return a user's progress of every trophy group of a title.
+    fn get_user_trophy_groups_v2<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        account_id: &'st str,
+        np_communication_id: &'st str,
+        np_service_name: NpServiceName,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url =
+                self.user_trophy_groups_v2_encode(account_id, np_communication_id, np_service_name);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return trophies defined by a trophy group. `group_id` can be `all`.
+    fn get_trophies_v2<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        np_communication_id: &'st str,
+        np_service_name: NpServiceName,
+        group_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.trophies_v2_encode(np_communication_id, np_service_name, group_id);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// return a user's earned status of the trophies in a trophy group. `group_id` can be `all`.
+    fn get_user_trophies_v2<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        account_id: &'st str,
+        np_communication_id: &'st str,
+        np_service_name: NpServiceName,
+        group_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.user_trophies_v2_encode(
+                account_id,
+                np_communication_id,
+                np_service_name,
+                group_id,
+            );
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
     /// return message threads of the account you used to login PSN network.
     /// `offset` can't be large than all existing threads count.
     fn get_message_threads<'a, T: DeserializeOwned + 'static>(
@@ -125,6 +433,33 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         })
     }
 
+    /// return the members of a thread without its events.
+    fn get_message_thread_members<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let url = self.message_thread_members_encode(thread_id);
+            self.get_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    /// Download the attachment of message event `event_index` in thread `thread_id`.
+    /// `content_key` is from `MessageEventDetail::attachment_content_key`.
+    fn download_attachment<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+        event_index: &'st str,
+        content_key: &'st str,
+    ) -> PSNFuture<'se, Result<Attachment, Self::Error>> {
+        Box::pin(async move {
+            let url = self.message_attachment_encode(thread_id, event_index, content_key);
+            self.get_bytes_by_url_encode(client, url.as_str()).await
+        })
+    }
+
     fn generate_message_thread<'se, 'st: 'se, T: DeserializeOwned + 'static>(
         &'se self,
         client: &'se Self::Client,
@@ -153,6 +488,109 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         })
     }
 
+    /// generate a new thread with `online_ids` and `self_online_id` as members.
+    fn generate_group_thread<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        online_ids: &'st [&'st str],
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let boundary = Self::generate_boundary();
+            let msg = serde_json::to_string(&GenerateNewThread::with_members(
+                online_ids,
+                self.self_online_id(),
+            ))
+            .unwrap_or_else(|_| "".to_owned());
+
+            let mut body = Vec::new();
+            write_string(&mut body, boundary.as_str(), "threadDetail", msg.as_str());
+
+            let url = self.generate_thread_encode();
+
+            self.post_by_multipart(client, boundary.as_str(), url.as_str(), body)
+                .await
+        })
+    }
+
+    fn add_thread_members<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+        online_ids: &'st [&'st str],
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.thread_members_encode(thread_id);
+            let body = serde_json::to_vec(&AddThreadMembers::new(online_ids)).unwrap_or_default();
+            self.post_by_url_encode(client, url.as_str(), body).await
+        })
+    }
+
+    fn remove_thread_member<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+        online_id: &'st str,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.thread_member_encode(thread_id, online_id);
+            self.del_by_url_encode(client, url.as_str()).await
+        })
+    }
+
+    fn set_thread_name<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+        thread_name: &'st str,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.thread_name_encode(thread_id);
+            let body = serde_json::to_vec(&SetThreadName::new(thread_name)).unwrap_or_default();
+            self.put_by_url_encode(client, url.as_str(), body).await
+        })
+    }
+
+    /// mark all messages in thread as read. It clears `NewArrivalEventDetail::new_arrival_event_flag`.
+    fn mark_thread_read<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.thread_seen_flag_encode(thread_id);
+            let body = serde_json::to_vec(&SetSeenFlag::new()).unwrap_or_default();
+            self.put_by_url_encode(client, url.as_str(), body).await
+        })
+    }
+
+    fn set_thread_favorite<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+        favorite: bool,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.thread_favorite_encode(thread_id);
+            let body = serde_json::to_vec(&SetFavorite::new(favorite)).unwrap_or_default();
+            self.put_by_url_encode(client, url.as_str(), body).await
+        })
+    }
+
+    /// turn push notification of thread on/off.
+    fn set_thread_notification<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        thread_id: &'st str,
+        push_notification: bool,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let url = self.thread_notification_encode(thread_id);
+            let body =
+                serde_json::to_vec(&SetNotification::new(push_notification)).unwrap_or_default();
+            self.put_by_url_encode(client, url.as_str(), body).await
+        })
+    }
+
     fn search_store_items<'se, 'st: 'se, T: DeserializeOwned + 'static>(
         &'se self,
         client: &'se Self::Client,
@@ -215,6 +653,28 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
         })
     }
 
+    /// send a sticker into thread `thread_id`. `StickerDetail` of a received sticker message can be used.
+    fn send_sticker<'se, 'st: 'se, T: DeserializeOwned + 'static>(
+        &'se self,
+        client: &'se Self::Client,
+        sticker: &'st StickerDetail,
+        thread_id: &'st str,
+    ) -> PSNFuture<'se, Result<T, Self::Error>> {
+        Box::pin(async move {
+            let boundary = Self::generate_boundary();
+            let url = self.send_message_encode(thread_id);
+
+            let msg = serde_json::to_string(&SendMessage::sticker(sticker))
+                .unwrap_or_else(|_| "".to_owned());
+
+            let mut body = Vec::new();
+            write_string(&mut body, &boundary, "messageEventDetail", msg.as_str());
+
+            self.post_by_multipart(client, boundary.as_str(), url.as_str(), body)
+                .await
+        })
+    }
+
     /// take `option<&str>` for `message` and `file path` to determine if the message is a text only or a image attached one.
     /// pass both as `None` will result in generating a new message thread body.
     fn multipart_body<'se, 'st: 'se>(
@@ -238,7 +698,11 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
                 return Ok(result);
             };
 
-            let event_category = if buf.is_some() { 3u8 } else { 1 };
+            let event_category = if buf.is_some() {
+                MessageEventKind::Image
+            } else {
+                MessageEventKind::Text
+            };
             let msg = serde_json::to_string(&SendMessage::new(msg, event_category))
                 .unwrap_or_else(|_| "".to_owned());
 
@@ -246,7 +710,11 @@ pub trait PSNRequest: Sized + Send + Sync + EncodeUrl + 'static {
 
             if let Some(buf) = buf {
                 result.extend_from_slice(b"Content-Disposition: form-data; name=\"imageData\"\r\n");
-                result.extend_from_slice(b"Content-Type: image/png\r\n");
+                // fall back to png for unknown format. `attachment::check_image` can be used to validate the buf beforehand.
+                let content_type = ImageFormat::sniff(buf)
+                    .unwrap_or(ImageFormat::Png)
+                    .content_type();
+                result.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
 
                 result
                     .extend_from_slice(format!("Content-Length: {}\r\n\r\n", buf.len()).as_bytes());
@@ -314,29 +782,137 @@ pub trait EncodeUrl {
         ]
     }
 
+    /// url for inspecting(GET) or revoking(DELETE) an `access_token` or `refresh_token`. PSN expects client id and secret as basic auth.
+    fn oauth_token_info_encode(token: &str) -> String {
+        format!("{}/{}", OAUTH_TOKEN_ENTRY, token)
+    }
+
     fn profile_encode(&self, online_id: &str) -> String {
         format!(
-            "https://{}{}{}/profile?fields=%40default,relation,requestMessageFlag,presence,%40personalDetail,trophySummary",
+            "https://{}{}{}/profile?fields=%40default,avatarUrls,isOfficiallyVerified,relation,requestMessageFlag,presences(%40titleInfo,lastOnlineDate),personalDetail(%40default,profilePictureUrls),trophySummary&avatarSizes=m&profilePictureSizes=m",
+            self.region(),
+            USERS_ENTRY,
+            online_id
+        )
+    }
+
+    fn friends_encode(&self, online_id: &str, query: &FriendQuery) -> String {
+        let mut url = format!(
+            "https://{}{}{}/friends/profiles2?fields={}&sort=onlineId&avatarSizes=m&profilePictureSizes=m&offset={}&limit={}",
+            self.region(),
+            USERS_ENTRY,
+            online_id,
+            query.fields_encode(),
+            query.get_offset(),
+            query.get_limit()
+        );
+        if query.get_online_only() {
+            url.push_str("&userFilter=online");
+        }
+        url
+    }
+
+    fn friend_requests_encode(
+        &self,
+        request_type: FriendRequestType,
+        query: &FriendQuery,
+    ) -> String {
+        format!(
+            "https://{}{}me/friends/{}/profiles2?fields={}&avatarSizes=m&profilePictureSizes=m&offset={}&limit={}",
+            self.region(),
+            USERS_ENTRY,
+            request_type.as_str(),
+            query.fields_encode(),
+            query.get_offset(),
+            query.get_limit()
+        )
+    }
+
+    fn presence_encode(&self, online_id: &str) -> String {
+        format!(
+            "https://{}{}{}/presence?type=primary",
+            self.region(),
+            USERS_ENTRY,
+            online_id
+        )
+    }
+
+    fn activity_feed_encode(&self, online_id: &str, page: u32) -> String {
+        format!(
+            "{}{}/feed/{}?includeComments=true&includeTaggedItems=true",
+            ACTIVITY_ENTRY, online_id, page
+        )
+    }
+
+    fn news_feed_encode(&self, page: u32) -> String {
+        format!(
+            "{}{}/news/{}?includeComments=true&includeTaggedItems=true",
+            ACTIVITY_ENTRY,
+            self.self_online_id(),
+            page
+        )
+    }
+
+    fn privacy_settings_encode(&self) -> String {
+        format!("https://{}{}me/privacySettings", self.region(), USERS_ENTRY)
+    }
+
+    fn block_list_encode(&self, offset: u32) -> String {
+        format!(
+            "https://{}{}me/blockList?fields=%40default&offset={}&limit=100",
+            self.region(),
+            USERS_ENTRY,
+            offset
+        )
+    }
+
+    fn block_user_encode(&self, online_id: &str) -> String {
+        format!(
+            "https://{}{}me/blockList/{}",
+            self.region(),
+            USERS_ENTRY,
+            online_id
+        )
+    }
+
+    fn account_id_encode(&self, online_id: &str) -> String {
+        format!(
+            "https://{}{}{}/profile2?fields=accountId,onlineId",
             self.region(),
             USERS_ENTRY,
             online_id
         )
     }
 
+    fn profile_by_account_id_encode(&self, account_id: &str) -> String {
+        format!("{}{}/profiles", USER_PROFILE_V2_ENTRY, account_id)
+    }
+
     fn trophy_summary_encode(&self, online_id: &str, offset: u32) -> String {
+        self.trophy_summary_query_encode(online_id, &TitleQuery::new().offset(offset))
+    }
+
+    fn trophy_summary_query_encode(&self, online_id: &str, query: &TitleQuery) -> String {
         format!(
-            "https://{}{}?fields=%40default&npLanguage={}&iconSize=m&platform=PS3,PSVITA,PS4&offset={}&limit=100&comparedUser={}",
+            "https://{}{}?fields=%40default&npLanguage={}&iconSize={}&platform={}&offset={}&limit={}&comparedUser={}",
             self.region(),
             USER_TROPHY_ENTRY,
-            self.language(),
-            offset,
+            query.get_lang().unwrap_or_else(|| self.language()),
+            query.get_icon_size().as_str(),
+            query.platforms_encode(),
+            query.get_offset(),
+            query.get_limit(),
             online_id
         )
     }
 
     fn trophy_set_encode(&self, online_id: &str, np_communication_id: &str) -> String {
+        self.trophy_group_set_encode(online_id, np_communication_id, "all")
+    }
+
+    fn trophy_groups_encode(&self, online_id: &str, np_communication_id: &str) -> String {
         format!(
-            "https://{}{}{}/trophyGroups/all/trophies?fields=%40default,trophyRare,trophyEarnedRate&npLanguage={}&comparedUser={}",
+            "https://{}{}{}/trophyGroups?fields=%40default,trophyGroupSmallIconUrl&npLanguage={}&iconSize=m&comparedUser={}",
             self.region(),
             USER_TROPHY_ENTRY,
             np_communication_id,
@@ -345,6 +921,91 @@ pub trait EncodeUrl {
         )
     }
 
+    /// `group_id` is `default` for base game, `001`, `002`... for DLC packs and `all` for every group.
+    fn trophy_group_set_encode(
+        &self,
+        online_id: &str,
+        np_communication_id: &str,
+        group_id: &str,
+    ) -> String {
+        format!(
+            "https://{}{}{}/trophyGroups/{}/trophies?fields=%40default,trophyRare,trophyEarnedRate&npLanguage={}&comparedUser={}",
+            self.region(),
+            USER_TROPHY_ENTRY,
+            np_communication_id,
+            group_id,
+            self.language(),
+            online_id
+        )
+    }
+
+    fn trophy_titles_v2_encode(&self, account_id: &str, offset: u32) -> String {
+        format!(
+            "{}users/{}/trophyTitles?offset={}&limit=100",
+            TROPHY_V2_ENTRY, account_id, offset
+        )
+    }
+
+    fn trophy_groups_v2_encode(
+        &self,
+        np_communication_id: &str,
+        np_service_name: NpServiceName,
+    ) -> String {
+        format!(
+            "{}npCommunicationIds/{}/trophyGroups?npServiceName={}",
+            TROPHY_V2_ENTRY,
+            np_communication_id,
+            np_service_name.as_str()
+        )
+    }
+
+    fn user_trophy_groups_v2_encode(
+        &self,
+        account_id: &str,
+        np_communication_id: &str,
+        np_service_name: NpServiceName,
+    ) -> String {
+        format!(
+            "{}users/{}/npCommunicationIds/{}/trophyGroups?npServiceName={}",
+            TROPHY_V2_ENTRY,
+            account_id,
+            np_communication_id,
+            np_service_name.as_str()
+        )
+    }
+
+    fn trophies_v2_encode(
+        &self,
+        np_communication_id: &str,
+        np_service_name: NpServiceName,
+        group_id: &str,
+    ) -> String {
+        format!(
+            "{}npCommunicationIds/{}/trophyGroups/{}/trophies?npServiceName={}",
+            TROPHY_V2_ENTRY,
+            np_communication_id,
+            group_id,
+            np_service_name.as_str()
+        )
+    }
+
+    fn user_trophies_v2_encode(
+        &self,
+        account_id: &str,
+        np_communication_id: &str,
+        np_service_name: NpServiceName,
+        group_id: &str,
+    ) -> String {
+        format!(
+            "{}users/{}/npCommunicationIds/{}/trophyGroups/{}/trophies?npServiceName={}",
+            TROPHY_V2_ENTRY,
+            account_id,
+            np_communication_id,
+            group_id,
+            np_service_name.as_str()
+        )
+    }
+
     fn message_threads_encode(&self, offset: u32) -> String {
         format!(
             "https://{}{}?offset={}",
@@ -363,6 +1024,15 @@ pub trait EncodeUrl {
         )
     }
 
+    fn message_thread_members_encode(&self, thread_id: &str) -> String {
+        format!(
+            "https://{}{}/{}?fields=threadMembers",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id
+        )
+    }
+
     fn generate_thread_encode(&self) -> String {
         format!("https://{}{}/", self.region(), MESSAGE_THREAD_ENTRY)
     }
@@ -376,6 +1046,78 @@ pub trait EncodeUrl {
         )
     }
 
+    /// `content_key` is the `contentKey` query of `MessageEventDetail::attached_media_path`. e.g. `image-data-0`.
+    fn message_attachment_encode(
+        &self,
+        thread_id: &str,
+        event_index: &str,
+        content_key: &str,
+    ) -> String {
+        format!(
+            "https://{}{}/{}/messages/{}?contentKey={}",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id,
+            event_index,
+            content_key
+        )
+    }
+
+    fn thread_members_encode(&self, thread_id: &str) -> String {
+        format!(
+            "https://{}{}/{}/users",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id
+        )
+    }
+
+    fn thread_member_encode(&self, thread_id: &str, online_id: &str) -> String {
+        format!(
+            "https://{}{}/{}/users/{}",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id,
+            online_id
+        )
+    }
+
+    fn thread_name_encode(&self, thread_id: &str) -> String {
+        format!(
+            "https://{}{}/{}/name",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id
+        )
+    }
+
+    fn thread_seen_flag_encode(&self, thread_id: &str) -> String {
+        format!(
+            "https://{}{}/{}/seenFlag",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id
+        )
+    }
+
+    fn thread_favorite_encode(&self, thread_id: &str) -> String {
+        format!(
+            "https://{}{}/{}/favorite",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id
+        )
+    }
+
+    fn thread_notification_encode(&self, thread_id: &str) -> String {
+        format!(
+            "https://{}{}/{}/notification",
+            self.region(),
+            MESSAGE_THREAD_ENTRY,
+            thread_id
+        )
+    }
+
     fn send_message_encode(&self, thread_id: &str) -> String {
         format!(
             "https://{}{}/{}/messages",
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
 use std::time::{Duration, Instant};
@@ -5,7 +6,9 @@ use std::time::{Duration, Instant};
 use reqwest::header;
 use serde::de::DeserializeOwned;
 
-use crate::metas::meta::OAUTH_TOKEN_ENTRY;
+use crate::attachment::Attachment;
+use crate::metas::meta::{CLIENT_ID, CLIENT_SECRET, OAUTH_TOKEN_ENTRY, SSO_COOKIE_ENTRY};
+use crate::models::{NpssoInfo, TitleSort, TokenInfo, TokenValidation};
 use crate::private_model::{PSNResponseError, Tokens};
 use crate::psn::PSNError;
 use crate::traits::{EncodeUrl, PSNRequest};
@@ -15,9 +18,9 @@ pub struct PSNInner {
     email: String,
     region: String,
     self_online_id: String,
-    access_token: Option<String>,
-    npsso: Option<String>,
-    refresh_token: Option<String>,
+    access_token: Option<Secret>,
+    npsso: Option<Secret>,
+    refresh_token: Option<Secret>,
     last_refresh_at: Option<Instant>,
     language: String,
 }
@@ -44,18 +47,18 @@ impl PSNInner {
 
     pub fn add_refresh_token(&mut self, refresh_token: String) -> &mut Self {
         if !refresh_token.is_empty() {
-            self.refresh_token = Some(refresh_token);
+            self.refresh_token = Some(refresh_token.into());
         }
         self
     }
 
     pub fn get_refresh_token(&self) -> Option<&str> {
-        self.refresh_token.as_deref()
+        self.refresh_token.as_ref().map(Secret::expose)
     }
 
     pub fn add_npsso(&mut self, npsso: String) -> &mut Self {
         if !npsso.is_empty() {
-            self.npsso = Some(npsso);
+            self.npsso = Some(npsso.into());
         }
         self
     }
@@ -90,12 +93,12 @@ impl PSNInner {
     }
 
     pub fn set_access_token(&mut self, access_token: Option<String>) -> &mut Self {
-        self.access_token = access_token;
+        self.access_token = access_token.map(Into::into);
         self
     }
 
     pub fn set_refresh_token(&mut self, refresh_token: Option<String>) -> &mut Self {
-        self.refresh_token = refresh_token;
+        self.refresh_token = refresh_token.map(Into::into);
         self
     }
 
@@ -104,6 +107,15 @@ impl PSNInner {
         self.last_refresh_at = Some(Instant::now());
     }
 
+    /// remove `access_token`, `refresh_token` and `npsso` from local. Tokens are not revoked on PSN server.
+    pub fn clear_credentials(&mut self) -> &mut Self {
+        self.access_token = None;
+        self.refresh_token = None;
+        self.npsso = None;
+        self.last_refresh_at = None;
+        self
+    }
+
     /// check if it's about time the access_token expires.
     pub fn should_refresh(&self) -> bool {
         if let Some(i) = self.last_refresh_at {
@@ -116,18 +128,276 @@ impl PSNInner {
     }
 }
 
+/// Query options of `get_titles_with_query` and `PSN::get_all_titles`. Default is all platforms, medium icon, `PSNInner` language, offset 0, limit 100 and no sort.
+#[derive(Debug, Clone)]
+pub struct TitleQuery {
+    platforms: Vec<Platform>,
+    icon_size: IconSize,
+    lang: Option<String>,
+    offset: u32,
+    limit: u32,
+    sort: Option<TitleSort>,
+}
+
+impl Default for TitleQuery {
+    fn default() -> TitleQuery {
+        TitleQuery {
+            platforms: vec![Platform::PS3, Platform::PSVITA, Platform::PS4],
+            icon_size: IconSize::Medium,
+            lang: None,
+            offset: 0,
+            limit: 100,
+            sort: None,
+        }
+    }
+}
+
+impl TitleQuery {
+    pub fn new() -> Self {
+        TitleQuery::default()
+    }
+
+    /// Only return titles of these platforms. Empty `platforms` is ignored.
+    pub fn platforms(mut self, platforms: Vec<Platform>) -> Self {
+        if !platforms.is_empty() {
+            self.platforms = platforms;
+        }
+        self
+    }
+
+    pub fn icon_size(mut self, icon_size: IconSize) -> Self {
+        self.icon_size = icon_size;
+        self
+    }
+
+    /// override the language of `PSNInner` for this query.
+    pub fn lang(mut self, lang: String) -> Self {
+        self.lang = Some(lang);
+        self
+    }
+
+    /// need a legit `offset`(offset can't be larger than the total trophy lists a user have).
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// max titles in one response. It's clamped to 1..=100 as PSN caps it at 100.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit.clamp(1, 100);
+        self
+    }
+
+    /// PSN has no server side sort. The titles are sorted locally after all pages are collected by `PSN::get_all_titles`.
+    /// `get_titles_with_query` ignores it and returns one page ordered by last update date.
+    pub fn sort(mut self, sort: TitleSort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn get_platforms(&self) -> &[Platform] {
+        &self.platforms
+    }
+
+    pub fn get_icon_size(&self) -> IconSize {
+        self.icon_size
+    }
+
+    pub fn get_lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    pub fn get_offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn get_limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn get_sort(&self) -> Option<TitleSort> {
+        self.sort
+    }
+
+    /// comma separated platforms for url query.
+    pub fn platforms_encode(&self) -> String {
+        self.platforms
+            .iter()
+            .map(Platform::as_str)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    PS3,
+    PSVITA,
+    PS4,
+}
+
+impl Platform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::PS3 => "PS3",
+            Platform::PSVITA => "PSVITA",
+            Platform::PS4 => "PS4",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl IconSize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IconSize::Small => "s",
+            IconSize::Medium => "m",
+            IconSize::Large => "l",
+        }
+    }
+}
+
+/// Query options of `get_friends` and `get_friend_requests`. Default is all friends with presence, offset 0 and limit 100.
+#[derive(Debug, Clone)]
+pub struct FriendQuery {
+    online_only: bool,
+    presence: bool,
+    offset: u32,
+    limit: u32,
+}
+
+impl Default for FriendQuery {
+    fn default() -> FriendQuery {
+        FriendQuery {
+            online_only: false,
+            presence: true,
+            offset: 0,
+            limit: 100,
+        }
+    }
+}
+
+impl FriendQuery {
+    pub fn new() -> Self {
+        FriendQuery::default()
+    }
+
+    /// Only return friends currently online.
+    pub fn online_only(mut self, online_only: bool) -> Self {
+        self.online_only = online_only;
+        self
+    }
+
+    /// Include `presences` of friends. Turn it off for a smaller response.
+    pub fn presence(mut self, presence: bool) -> Self {
+        self.presence = presence;
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// max friends in one response. capped at 100.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit.min(100);
+        self
+    }
+
+    pub fn get_online_only(&self) -> bool {
+        self.online_only
+    }
+
+    pub fn get_presence(&self) -> bool {
+        self.presence
+    }
+
+    pub fn get_offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn get_limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// profile fields for url query.
+    pub fn fields_encode(&self) -> &'static str {
+        if self.presence {
+            "onlineId,accountId,avatarUrls,plus,isOfficiallyVerified,personalDetail(%40default,profilePictureUrls),primaryOnlineStatus,presences(%40titleInfo,lastOnlineDate),relation"
+        } else {
+            "onlineId,accountId,avatarUrls,plus,isOfficiallyVerified,personalDetail(%40default,profilePictureUrls),relation"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FriendRequestType {
+    /// Requests sent to the logged in account.
+    Received,
+    /// Requests sent by the logged in account.
+    Sent,
+}
+
+impl FriendRequestType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FriendRequestType::Received => "receivedRequests",
+            FriendRequestType::Sent => "sentRequests",
+        }
+    }
+}
+
+/// A wrapper for credentials like `npsso`, `access_token` and `refresh_token`.
+/// `Debug` and `Display` output are redacted. Use `Secret::expose` when the raw value is needed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Secret(secret.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<String> for Secret {
+    fn from(secret: String) -> Self {
+        Secret(secret)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
 impl EncodeUrl for PSNInner {
     fn npsso(&self) -> Option<&str> {
-        self.npsso.as_deref()
+        self.npsso.as_ref().map(Secret::expose)
     }
 
     fn access_token(&self) -> Option<&str> {
-        self.access_token.as_deref()
+        self.access_token.as_ref().map(Secret::expose)
     }
 
     fn refresh_token(&self) -> &str {
-        self.refresh_token
-            .as_deref()
+        self.get_refresh_token()
             .expect("refresh_token can't be None when npsso code is not working")
     }
 
@@ -153,9 +423,7 @@ impl PSNRequest for PSNInner {
         client: &'se Self::Client,
     ) -> PSNFuture<'se, Result<(), Self::Error>> {
         Box::pin(async move {
-            let npsso = self
-                .npsso()
-                .ok_or_else(|| PSNError::InvalidNpsso("No npsso found".into()))?;
+            let npsso = self.npsso().ok_or(PSNError::NoNpsso)?;
 
             let string_body = serde_urlencoded::to_string(&Self::oauth_token_encode())
                 .expect("Failed to parse string body for first authentication");
@@ -171,7 +439,7 @@ impl PSNRequest for PSNInner {
                 .await?;
 
             if tokens.access_token.is_none() || tokens.refresh_token.is_none() {
-                return Err(PSNError::InvalidNpsso(npsso.into()));
+                return Err(PSNError::InvalidNpsso(Secret::new(npsso)));
             }
 
             self.set_access_token(tokens.access_token)
@@ -200,7 +468,7 @@ impl PSNRequest for PSNInner {
                 .await?;
 
             if tokens.access_token.is_none() {
-                return Err(PSNError::InvalidRefresh(self.refresh_token().into()));
+                return Err(PSNError::InvalidRefresh(Secret::new(self.refresh_token())));
             }
 
             self.set_access_token(tokens.access_token)
@@ -211,6 +479,73 @@ impl PSNRequest for PSNInner {
         })
     }
 
+    fn validate_tokens<'se>(
+        &'se self,
+        client: &'se Self::Client,
+    ) -> PSNFuture<'se, Result<TokenValidation, Self::Error>> {
+        Box::pin(async move {
+            let access_token = match self.access_token() {
+                Some(token) => token_info(client, token).await?,
+                None => None,
+            };
+
+            let refresh_token = match self.get_refresh_token() {
+                Some(token) => token_info(client, token).await?,
+                None => None,
+            };
+
+            let npsso = match self.npsso() {
+                Some(npsso) => {
+                    let res = client
+                        .get(SSO_COOKIE_ENTRY)
+                        .header("Cookie", format!("npsso={}", npsso))
+                        .send()
+                        .await?;
+
+                    if is_rejected(res.status()) {
+                        None
+                    } else if res.status() != 200 {
+                        return Err(unexpected_status("check npsso", res.status()));
+                    } else {
+                        Some(res.json::<NpssoInfo>().await?)
+                    }
+                }
+                None => None,
+            };
+
+            Ok(TokenValidation {
+                access_token,
+                refresh_token,
+                npsso,
+            })
+        })
+    }
+
+    fn logout<'se>(
+        &'se mut self,
+        client: &'se Self::Client,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let mut result = Ok(());
+
+            if let Some(token) = self.access_token() {
+                if let Err(e) = revoke_token(client, token).await {
+                    result = Err(e);
+                }
+            }
+
+            if let Some(token) = self.get_refresh_token() {
+                if let Err(e) = revoke_token(client, token).await {
+                    result = Err(e);
+                }
+            }
+
+            self.clear_credentials();
+
+            result
+        })
+    }
+
     fn get_by_url_encode<'se, 'st: 'se, T: DeserializeOwned + 'static>(
         &'se self,
         client: &'se Self::Client,
@@ -235,7 +570,7 @@ impl PSNRequest for PSNInner {
 
                 if res.status() != 200 {
                     let e = res.json::<PSNResponseError>().await?;
-                    Err(PSNError::FromPSN(e.error.message.into_boxed_str()))
+                    Err(e.into())
                 } else {
                     let res = res.json().await?;
                     Ok(res)
@@ -264,7 +599,67 @@ impl PSNRequest for PSNInner {
 
             if res.status() != 204 {
                 let e = res.json::<PSNResponseError>().await?;
-                Err(PSNError::FromPSN(e.error.message.into_boxed_str()))
+                Err(e.into())
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    fn put_by_url_encode<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        url: &'st str,
+        body: Vec<u8>,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let res = client
+                .put(url)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(
+                    header::AUTHORIZATION,
+                    format!(
+                        "Bearer {}",
+                        self.access_token().expect("access_token is None")
+                    ),
+                )
+                .body(body)
+                .send()
+                .await?;
+
+            if !res.status().is_success() {
+                let e = res.json::<PSNResponseError>().await?;
+                Err(e.into())
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    fn post_by_url_encode<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        url: &'st str,
+        body: Vec<u8>,
+    ) -> PSNFuture<'se, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let res = client
+                .post(url)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(
+                    header::AUTHORIZATION,
+                    format!(
+                        "Bearer {}",
+                        self.access_token().expect("access_token is None")
+                    ),
+                )
+                .body(body)
+                .send()
+                .await?;
+
+            if !res.status().is_success() {
+                let e = res.json::<PSNResponseError>().await?;
+                Err(e.into())
             } else {
                 Ok(())
             }
@@ -300,7 +695,7 @@ impl PSNRequest for PSNInner {
 
                 if res.status() != 200 {
                     let e = res.json::<PSNResponseError>().await?;
-                    Err(PSNError::FromPSN(e.error.message.into_boxed_str()))
+                    Err(e.into())
                 } else {
                     let res = res.json().await?;
                     Ok(res)
@@ -309,10 +704,119 @@ impl PSNRequest for PSNInner {
         )
     }
 
+    fn get_bytes_by_url_encode<'se, 'st: 'se>(
+        &'se self,
+        client: &'se Self::Client,
+        url: &'st str,
+    ) -> PSNFuture<'se, Result<Attachment, Self::Error>> {
+        Box::pin(async move {
+            let mut res = self.bytes_response(client, url).await?;
+
+            let content_type = content_type(&res);
+            let mut buf = Vec::new();
+            while let Some(chunk) = res.chunk().await? {
+                buf.extend_from_slice(&chunk);
+            }
+
+            Ok(Attachment { content_type, buf })
+        })
+    }
+
     fn read_path(path: &str) -> PSNFuture<Result<Vec<u8>, Self::Error>> {
         Box::pin(async move { tokio::fs::read(path).await.map_err(PSNError::FromStd) })
     }
 }
 
+impl PSNInner {
+    // binary content is not json so it's requested without the json content type and error body.
+    // `PSN::download_attachment_to_path` streams the response to file so it's not collected here.
+    pub(crate) async fn bytes_response(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+    ) -> Result<reqwest::Response, PSNError> {
+        let res = client
+            .get(url)
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", self.access_token().unwrap_or_default()),
+            )
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(PSNError::FromPSN(
+                format!(
+                    "Failed to download content. Response status: {}",
+                    res.status()
+                )
+                .into(),
+            ));
+        }
+
+        Ok(res)
+    }
+}
+
+pub(crate) fn content_type(res: &reqwest::Response) -> Option<String> {
+    res.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
+
+// PSN oauth server answers with 4xx status when the token is expired or revoked.
+// other statuses like 429 and 5xx don't tell the token is dead so they are errors.
+async fn token_info(client: &reqwest::Client, token: &str) -> Result<Option<TokenInfo>, PSNError> {
+    let res = client
+        .get(&PSNInner::oauth_token_info_encode(token))
+        .basic_auth(CLIENT_ID, Some(CLIENT_SECRET))
+        .send()
+        .await
+        .map_err(redact_url)?;
+
+    if is_rejected(res.status()) {
+        Ok(None)
+    } else if res.status() != 200 {
+        Err(unexpected_status("check token", res.status()))
+    } else {
+        Ok(Some(res.json().await.map_err(redact_url)?))
+    }
+}
+
+fn is_rejected(status: reqwest::StatusCode) -> bool {
+    matches!(status.as_u16(), 400 | 401 | 403 | 404)
+}
+
+fn unexpected_status(action: &str, status: reqwest::StatusCode) -> PSNError {
+    PSNError::FromPSN(format!("Failed to {}. Response status: {}", action, status).into())
+}
+
+async fn revoke_token(client: &reqwest::Client, token: &str) -> Result<(), PSNError> {
+    let res = client
+        .delete(&PSNInner::oauth_token_info_encode(token))
+        .basic_auth(CLIENT_ID, Some(CLIENT_SECRET))
+        .send()
+        .await
+        .map_err(redact_url)?;
+
+    if !res.status().is_success() {
+        Err(PSNError::FromPSN(
+            format!("Failed to revoke token. Response status: {}", res.status()).into(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// `reqwest::Error` displays the request url and the token is in the url path of oauth token endpoint.
+fn redact_url(e: reqwest::Error) -> PSNError {
+    let msg = match std::error::Error::source(&e) {
+        Some(source) => format!("Request to PSN oauth server failed: {}", source),
+        None => "Request to PSN oauth server failed".to_owned(),
+    };
+    PSNError::FromPSN(msg.into())
+}
+
 /// type alias to stop clippy from complaining
 pub type PSNFuture<'s, T> = Pin<Box<dyn Future<Output = T> + Send + 's>>;
@@ -0,0 +1,770 @@
+//! Watchers poll PSN through `PSN` pool on an interval and yield the changes as a `Stream`.
+//! `TrophyWatcher` yields newly earned trophies, `PresenceWatcher` yields online/offline and in game changes
+//! and `MessageListener` yields incoming messages of the logged in account.
+//! An error of one user, title or thread is yielded and doesn't stop polling the others.
+//!
+//! # Example:
+//!```no_run
+//!use futures::StreamExt;
+//!use psn_api_rs::{psn::PSN, watcher::TrophyWatcher};
+//!
+//!async fn watch_trophies(psn: PSN) {
+//!    let mut events = Box::pin(
+//!        TrophyWatcher::new(psn, vec!["Hakoom".into()])
+//!            .interval(std::time::Duration::from_secs(300))
+//!            .into_stream(),
+//!    );
+//!
+//!    while let Some(event) = events.next().await {
+//!        match event {
+//!            Ok(e) => println!("{} earned {:?} in {}", e.online_id, e.trophy.trophy_name, e.title),
+//!            Err(e) => println!("{}", e),
+//!        }
+//!    }
+//!}
+//!```
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::time::Duration;
+
+use futures::stream::{self, Stream};
+use tokio::time::delay_for;
+
+use crate::models::{
+    MessageEventDetail, MessageEventKind, MessageThread, MessageThreadsSummary, Presence,
+    StickerDetail, ThreadMember, Trophy, TrophySet, TrophyTitle, TrophyTitles, UserPresence,
+};
+use crate::psn::{PSNError, PSN};
+
+/// Yielded by `TrophyWatcher` when `online_id` earned a new trophy.
+#[derive(Debug)]
+pub struct TrophyEarned {
+    pub online_id: String,
+    pub np_communication_id: String,
+    pub title: String,
+    pub trophy: Trophy,
+    pub earned_date: String,
+}
+
+#[cfg(feature = "chrono")]
+impl TrophyEarned {
+    pub fn earned_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::models::parse_date(&self.earned_date)
+    }
+}
+
+/// The last seen state of all watched users' trophy titles.
+/// It can be stored and passed to `TrophyWatcher::with_state` so the watcher can resume after restart.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrophyWatcherState {
+    /// online_id -> np_communication_id -> title state
+    pub users: HashMap<String, HashMap<String, TitleState>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleState {
+    pub last_update_date: String,
+    pub earned: u32,
+}
+
+/// Poll the trophy titles of a list of users and yield `TrophyEarned` events.
+///
+/// Only the first page(100 titles ordered by last update) of trophy titles is checked on every poll and
+/// trophy sets are only fetched for the titles with changed `last_update_date` or earned counts.
+/// The first poll of a user not in state only records the state without yielding events.
+/// A title not in state(e.g. an old game played again) only yields the trophies earned after the newest title update of last poll.
+pub struct TrophyWatcher {
+    psn: PSN,
+    online_ids: Vec<String>,
+    interval: Duration,
+    state: TrophyWatcherState,
+}
+
+impl TrophyWatcher {
+    pub fn new(psn: PSN, online_ids: Vec<String>) -> Self {
+        TrophyWatcher {
+            psn,
+            online_ids,
+            interval: Duration::from_secs(60),
+            state: TrophyWatcherState::default(),
+        }
+    }
+
+    /// poll interval. default is 60 seconds.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_state(mut self, state: TrophyWatcherState) -> Self {
+        self.state = state;
+        self
+    }
+
+    pub fn state(&self) -> &TrophyWatcherState {
+        &self.state
+    }
+
+    /// Poll all users once and return the new trophies.
+    pub async fn poll(&mut self) -> Vec<Result<TrophyEarned, PSNError>> {
+        let mut events = Vec::new();
+
+        for online_id in self.online_ids.iter() {
+            events.extend(poll_user(&self.psn, &mut self.state, online_id).await);
+        }
+
+        events
+    }
+
+    /// Poll on interval forever. The first poll starts immediately.
+    pub fn into_stream(self) -> impl Stream<Item = Result<TrophyEarned, PSNError>> {
+        let interval = self.interval;
+        poll_stream(self, interval, |mut watcher| async move {
+            let events = watcher.poll().await;
+            (watcher, events)
+        })
+    }
+}
+
+async fn poll_user(
+    psn: &PSN,
+    state: &mut TrophyWatcherState,
+    online_id: &str,
+) -> Vec<Result<TrophyEarned, PSNError>> {
+    let titles: TrophyTitles = match psn.get_titles(online_id, 0).await {
+        Ok(titles) => titles,
+        Err(e) => return vec![Err(e)],
+    };
+
+    let is_new_user = !state.users.contains_key(online_id);
+    let user_state = state.users.entry(online_id.to_owned()).or_default();
+
+    let watermark = watermark(user_state);
+
+    let mut events = Vec::new();
+
+    for title in titles.trophy_titles.into_iter() {
+        let since = match title_diff(user_state, watermark.as_deref(), &title) {
+            TitleDiff::Unchanged => continue,
+            TitleDiff::Since(since) => since,
+        };
+
+        if !is_new_user {
+            let set = match psn
+                .get_trophy_set::<TrophySet>(online_id, &title.np_communication_id)
+                .await
+            {
+                Ok(set) => set,
+                Err(e) => {
+                    // skip updating state so the title would be checked again on next poll.
+                    events.push(Err(e));
+                    continue;
+                }
+            };
+
+            events.extend(earned_since(online_id, &title, set, since).map(Ok));
+        }
+
+        user_state.insert(
+            title.np_communication_id,
+            TitleState {
+                earned: title.title_detail.earned_trophies.total(),
+                last_update_date: title.title_detail.last_update_date,
+            },
+        );
+    }
+
+    events
+}
+
+// the newest title update seen in previous polls.
+// only the first page of titles is watched so a title missing from state could be an old game played again.
+fn watermark(user_state: &HashMap<String, TitleState>) -> Option<String> {
+    user_state
+        .values()
+        .map(|s| s.last_update_date.as_str())
+        .max()
+        .map(String::from)
+}
+
+enum TitleDiff<'a> {
+    Unchanged,
+    /// Trophies earned after the date are new. All earned trophies are new when it's `None`.
+    Since(Option<&'a str>),
+}
+
+// a title missing from state is diffed against `watermark` so the trophies earned before last poll are not reported again.
+fn title_diff<'a>(
+    user_state: &'a HashMap<String, TitleState>,
+    watermark: Option<&'a str>,
+    title: &TrophyTitle,
+) -> TitleDiff<'a> {
+    let detail = &title.title_detail;
+
+    match user_state.get(&title.np_communication_id) {
+        Some(s)
+            if s.last_update_date == detail.last_update_date
+                && s.earned == detail.earned_trophies.total() =>
+        {
+            TitleDiff::Unchanged
+        }
+        Some(s) => TitleDiff::Since(Some(&s.last_update_date)),
+        None => TitleDiff::Since(watermark),
+    }
+}
+
+fn earned_since<'a>(
+    online_id: &'a str,
+    title: &'a TrophyTitle,
+    set: TrophySet,
+    since: Option<&'a str>,
+) -> impl Iterator<Item = TrophyEarned> + 'a {
+    set.trophies.into_iter().filter_map(move |trophy| {
+        let earned_date = match trophy.user_info.earned_date.as_ref() {
+            Some(date) if trophy.user_info.earned => date.to_owned(),
+            _ => return None,
+        };
+
+        if since.map(|s| earned_date.as_str() > s).unwrap_or(true) {
+            Some(TrophyEarned {
+                online_id: online_id.to_owned(),
+                np_communication_id: title.np_communication_id.clone(),
+                title: title.trophy_title_name.clone(),
+                trophy,
+                earned_date,
+            })
+        } else {
+            None
+        }
+    })
+}
+
+/// Yielded by `PresenceWatcher` when a user's presence changed.
+#[derive(Debug)]
+pub enum PresenceEvent {
+    WentOnline {
+        online_id: String,
+        presence: Presence,
+    },
+    WentOffline {
+        online_id: String,
+        presence: Presence,
+    },
+    /// The user launched a title. It's yielded after `WentOnline` if the user is in game when coming online.
+    StartedPlaying {
+        online_id: String,
+        presence: Presence,
+    },
+}
+
+/// Poll the presences of a list of users and yield `PresenceEvent`s.
+///
+/// `Standby` is treated as offline. The first poll of a user only records the presence without yielding events.
+pub struct PresenceWatcher {
+    psn: PSN,
+    online_ids: Vec<String>,
+    interval: Duration,
+    state: HashMap<String, Presence>,
+}
+
+impl PresenceWatcher {
+    pub fn new(psn: PSN, online_ids: Vec<String>) -> Self {
+        PresenceWatcher {
+            psn,
+            online_ids,
+            interval: Duration::from_secs(60),
+            state: HashMap::new(),
+        }
+    }
+
+    /// poll interval. default is 60 seconds.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The last seen presence of users.
+    pub fn state(&self) -> &HashMap<String, Presence> {
+        &self.state
+    }
+
+    /// Poll all users once and return the changes.
+    pub async fn poll(&mut self) -> Vec<Result<PresenceEvent, PSNError>> {
+        let mut events = Vec::new();
+
+        for online_id in self.online_ids.iter() {
+            let presence = match self.psn.get_presence::<UserPresence>(online_id).await {
+                Ok(p) => p.presence,
+                Err(e) => {
+                    events.push(Err(e));
+                    continue;
+                }
+            };
+
+            if let Some(last) = self.state.get(online_id) {
+                events.extend(presence_events(online_id, last, &presence).map(Ok));
+            }
+
+            self.state.insert(online_id.to_owned(), presence);
+        }
+
+        events
+    }
+
+    /// Poll on interval forever. The first poll starts immediately.
+    pub fn into_stream(self) -> impl Stream<Item = Result<PresenceEvent, PSNError>> {
+        let interval = self.interval;
+        poll_stream(self, interval, |mut watcher| async move {
+            let events = watcher.poll().await;
+            (watcher, events)
+        })
+    }
+}
+
+fn presence_events(
+    online_id: &str,
+    last: &Presence,
+    now: &Presence,
+) -> impl Iterator<Item = PresenceEvent> {
+    let went_online = !last.is_online() && now.is_online();
+    let went_offline = last.is_online() && !now.is_online();
+    let started_playing = now.is_online()
+        && now.np_title_id.is_some()
+        && (went_online || now.np_title_id != last.np_title_id);
+
+    let online = if went_online {
+        Some(PresenceEvent::WentOnline {
+            online_id: online_id.to_owned(),
+            presence: now.clone(),
+        })
+    } else if went_offline {
+        Some(PresenceEvent::WentOffline {
+            online_id: online_id.to_owned(),
+            presence: now.clone(),
+        })
+    } else {
+        None
+    };
+
+    let playing = if started_playing {
+        Some(PresenceEvent::StartedPlaying {
+            online_id: online_id.to_owned(),
+            presence: now.clone(),
+        })
+    } else {
+        None
+    };
+
+    online.into_iter().chain(playing)
+}
+
+/// Yielded by `MessageListener` for every new message sent by other users.
+#[derive(Debug)]
+pub struct IncomingMessage {
+    pub thread_id: String,
+    pub event_index: String,
+    pub sender: ThreadMember,
+    pub kind: MessageEventKind,
+    pub text: Option<String>,
+    /// Only for `MessageEventKind::Sticker`.
+    pub sticker: Option<StickerDetail>,
+    /// Use `PSN::download_attachment` to get the attachment.
+    pub attached_media_path: Option<String>,
+    pub post_date: String,
+}
+
+#[cfg(feature = "chrono")]
+impl IncomingMessage {
+    pub fn post_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::models::parse_date(&self.post_date)
+    }
+}
+
+/// The last seen state of all message threads.
+/// It can be stored and passed to `MessageListener::with_state` so the listener can resume after restart.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageListenerState {
+    /// thread_id -> thread state
+    pub threads: HashMap<String, ThreadState>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadState {
+    pub thread_modified_date: String,
+    pub event_index: u64,
+}
+
+/// Poll the message threads of the logged in account and yield `IncomingMessage`s.
+///
+/// Only the first page of threads(ordered by modified date) is checked on every poll and
+/// thread events are only fetched for the threads with changed `thread_modified_date`.
+/// Messages sent by `self_online_id` are skipped. The first poll only records the state without yielding messages.
+/// A thread not in state(e.g. an old conversation moved back to the first page) only yields the messages posted after
+/// the newest thread update of last poll.
+///
+/// All the PSNInners in `PSN` pool must be the same account otherwise threads of other accounts are mixed in.
+pub struct MessageListener {
+    psn: PSN,
+    self_online_id: String,
+    interval: Duration,
+    state: MessageListenerState,
+    first_poll: bool,
+}
+
+impl MessageListener {
+    pub fn new(psn: PSN, self_online_id: String) -> Self {
+        MessageListener {
+            psn,
+            self_online_id,
+            interval: Duration::from_secs(10),
+            state: MessageListenerState::default(),
+            first_poll: true,
+        }
+    }
+
+    /// poll interval. default is 10 seconds.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Resume from a stored state. Messages newer than the state are yielded on the first poll.
+    pub fn with_state(mut self, state: MessageListenerState) -> Self {
+        self.state = state;
+        self.first_poll = false;
+        self
+    }
+
+    pub fn state(&self) -> &MessageListenerState {
+        &self.state
+    }
+
+    /// Poll threads once and return the new messages.
+    pub async fn poll(&mut self) -> Vec<Result<IncomingMessage, PSNError>> {
+        let summary: MessageThreadsSummary = match self.psn.get_message_threads(0).await {
+            Ok(summary) => summary,
+            Err(e) => return vec![Err(e)],
+        };
+
+        let first_poll = self.first_poll;
+        self.first_poll = false;
+
+        let watermark = self
+            .state
+            .threads
+            .values()
+            .map(|s| s.thread_modified_date.as_str())
+            .max()
+            .map(String::from);
+
+        let mut messages = Vec::new();
+
+        for thread in summary.threads.into_iter() {
+            let thread_state = match self.state.threads.get(&thread.thread_id) {
+                Some(s) if s.thread_modified_date == thread.thread_modified_date => continue,
+                thread_state => thread_state,
+            };
+
+            let detail: MessageThread = match self.psn.get_message_thread(&thread.thread_id).await {
+                Ok(detail) => detail,
+                Err(e) => {
+                    // skip updating state so the thread would be checked again on next poll.
+                    messages.push(Err(e));
+                    continue;
+                }
+            };
+
+            let (event_index, events) = new_events(
+                thread_state,
+                watermark.as_deref(),
+                detail
+                    .thread_events
+                    .into_iter()
+                    .map(|e| e.message_event_detail),
+            );
+
+            if !first_poll {
+                for event in events.into_iter() {
+                    if event
+                        .sender
+                        .online_id
+                        .eq_ignore_ascii_case(&self.self_online_id)
+                    {
+                        continue;
+                    }
+
+                    messages.push(Ok(IncomingMessage {
+                        thread_id: thread.thread_id.clone(),
+                        event_index: event.event_index,
+                        sender: event.sender,
+                        kind: event.event_category_code,
+                        text: event.message_detail.body,
+                        sticker: event.message_detail.sticker_detail,
+                        attached_media_path: event.attached_media_path,
+                        post_date: event.post_date,
+                    }));
+                }
+            }
+
+            self.state.threads.insert(
+                thread.thread_id,
+                ThreadState {
+                    thread_modified_date: thread.thread_modified_date,
+                    event_index,
+                },
+            );
+        }
+
+        messages
+    }
+
+    /// Poll on interval forever. The first poll starts immediately.
+    pub fn into_stream(self) -> impl Stream<Item = Result<IncomingMessage, PSNError>> {
+        let interval = self.interval;
+        poll_stream(self, interval, |mut watcher| async move {
+            let events = watcher.poll().await;
+            (watcher, events)
+        })
+    }
+}
+
+// the events of a thread newer than its state ordered by event index and the last event index of the thread.
+// a thread missing from state could be an old conversation moved back to the first page of threads
+// so its events are filtered by `watermark`(the newest thread update of last poll) instead.
+fn new_events(
+    thread_state: Option<&ThreadState>,
+    watermark: Option<&str>,
+    events: impl Iterator<Item = MessageEventDetail>,
+) -> (u64, Vec<MessageEventDetail>) {
+    let last_index = thread_state.map(|s| s.event_index).unwrap_or(0);
+    let mut event_index = last_index;
+
+    let mut events = events
+        .filter_map(|e| e.event_index.parse::<u64>().ok().map(|index| (index, e)))
+        .inspect(|(index, _)| event_index = event_index.max(*index))
+        .filter(|(index, e)| match thread_state {
+            Some(_) => *index > last_index,
+            None => watermark.map(|w| e.post_date.as_str() > w).unwrap_or(true),
+        })
+        .collect::<Vec<_>>();
+    events.sort_by_key(|(index, _)| *index);
+
+    (event_index, events.into_iter().map(|(_, e)| e).collect())
+}
+
+// poll on `interval` forever and yield the polled items one by one. the first poll starts immediately.
+// `poll` takes the watcher by value and gives it back with the items so the future doesn't borrow from the stream state.
+fn poll_stream<W, T, F, Fut>(watcher: W, interval: Duration, poll: F) -> impl Stream<Item = T>
+where
+    F: FnMut(W) -> Fut,
+    Fut: Future<Output = (W, Vec<T>)>,
+{
+    stream::unfold(
+        (watcher, poll, VecDeque::new(), true),
+        move |(mut watcher, mut poll, mut queue, mut first)| async move {
+            loop {
+                if let Some(item) = queue.pop_front() {
+                    return Some((item, (watcher, poll, queue, first)));
+                }
+
+                if !first {
+                    delay_for(interval).await;
+                }
+                first = false;
+
+                let (w, items) = poll(watcher).await;
+                watcher = w;
+                queue.extend(items);
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn title(id: &str, last_update_date: &str, bronze: u32) -> TrophyTitle {
+        serde_json::from_value(serde_json::json!({
+            "npCommunicationId": id,
+            "trophyTitleName": id,
+            "trophyTitleDetail": "",
+            "trophyTitleIconUrl": "",
+            "trophyTitlePlatfrom": "PS4",
+            "hasTrophyGroups": false,
+            "definedTrophies": { "platinum": 0, "gold": 0, "silver": 0, "bronze": 10 },
+            "comparedUser": {
+                "progress": 0,
+                "earnedTrophies": { "platinum": 0, "gold": 0, "silver": 0, "bronze": bronze },
+                "lastUpdateDate": last_update_date
+            }
+        }))
+        .unwrap()
+    }
+
+    fn set(earned_dates: &[Option<&str>]) -> TrophySet {
+        let trophies = earned_dates
+            .iter()
+            .enumerate()
+            .map(|(id, date)| {
+                serde_json::json!({
+                    "trophyId": id,
+                    "trophyHidden": false,
+                    "trophyRare": 3,
+                    "trophyEarnedRate": "50.0",
+                    "comparedUser": { "onlineId": "Hakoom", "earned": date.is_some(), "earnedDate": date }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::from_value(serde_json::json!({ "trophies": trophies })).unwrap()
+    }
+
+    fn state(titles: &[(&str, &str, u32)]) -> HashMap<String, TitleState> {
+        titles
+            .iter()
+            .map(|(id, date, earned)| {
+                (
+                    (*id).to_owned(),
+                    TitleState {
+                        last_update_date: (*date).to_owned(),
+                        earned: *earned,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn diff(
+        user_state: &HashMap<String, TitleState>,
+        title: &TrophyTitle,
+        set: TrophySet,
+    ) -> Option<Vec<u8>> {
+        let watermark = watermark(user_state);
+        match title_diff(user_state, watermark.as_deref(), title) {
+            TitleDiff::Unchanged => None,
+            TitleDiff::Since(since) => Some(
+                earned_since("Hakoom", title, set, since)
+                    .map(|e| e.trophy.trophy_id)
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn unchanged_title() {
+        let user_state = state(&[("NPWR1", "2020-05-01T00:00:00Z", 1)]);
+        let title = title("NPWR1", "2020-05-01T00:00:00Z", 1);
+
+        assert_eq!(diff(&user_state, &title, set(&[])), None);
+    }
+
+    #[test]
+    fn changed_title() {
+        let user_state = state(&[("NPWR1", "2020-05-01T00:00:00Z", 1)]);
+        let title = title("NPWR1", "2020-05-02T00:00:00Z", 2);
+        let set = set(&[
+            Some("2020-05-01T00:00:00Z"),
+            Some("2020-05-02T00:00:00Z"),
+            None,
+        ]);
+
+        assert_eq!(diff(&user_state, &title, set), Some(vec![1]));
+    }
+
+    #[test]
+    fn old_title_played_again() {
+        let user_state = state(&[
+            ("NPWR1", "2020-05-01T00:00:00Z", 1),
+            ("NPWR2", "2020-06-01T00:00:00Z", 1),
+        ]);
+        // earned long ago and not in the watched first page of last poll.
+        let title = title("NPWR3", "2020-06-02T00:00:00Z", 3);
+        let set = set(&[
+            Some("2018-01-01T00:00:00Z"),
+            Some("2019-01-01T00:00:00Z"),
+            Some("2020-06-02T00:00:00Z"),
+        ]);
+
+        assert_eq!(diff(&user_state, &title, set), Some(vec![2]));
+    }
+
+    #[test]
+    fn first_title_of_user() {
+        let user_state = state(&[]);
+        let title = title("NPWR1", "2020-05-01T00:00:00Z", 1);
+        let set = set(&[Some("2020-05-01T00:00:00Z")]);
+
+        assert_eq!(diff(&user_state, &title, set), Some(vec![0]));
+    }
+
+    fn event(index: u64, post_date: &str) -> MessageEventDetail {
+        serde_json::from_value(serde_json::json!({
+            "eventIndex": index.to_string(),
+            "postDate": post_date,
+            "eventCategoryCode": 1,
+            "altEventCategoryCode": 1,
+            "sender": { "accountId": "1", "onlineId": "Hakoom" },
+            "messageDetail": { "body": "hi" }
+        }))
+        .unwrap()
+    }
+
+    fn indexes(events: Vec<MessageEventDetail>) -> Vec<String> {
+        events.into_iter().map(|e| e.event_index).collect()
+    }
+
+    #[test]
+    fn known_thread_events() {
+        let thread_state = ThreadState {
+            thread_modified_date: "2020-05-01T00:00:00Z".to_owned(),
+            event_index: 2,
+        };
+        let events = vec![
+            event(3, "2020-05-02T00:00:00Z"),
+            event(1, "2020-04-01T00:00:00Z"),
+            event(2, "2020-05-01T00:00:00Z"),
+        ];
+
+        let (event_index, events) = new_events(
+            Some(&thread_state),
+            Some("2020-06-01T00:00:00Z"),
+            events.into_iter(),
+        );
+
+        assert_eq!(event_index, 3);
+        assert_eq!(indexes(events), vec!["3"]);
+    }
+
+    #[test]
+    fn thread_first_seen_after_first_poll() {
+        // an old conversation moved back to the first page of threads.
+        let events = vec![
+            event(1, "2019-01-01T00:00:00Z"),
+            event(2, "2019-02-01T00:00:00Z"),
+            event(3, "2020-06-02T00:00:00Z"),
+        ];
+
+        let (event_index, events) =
+            new_events(None, Some("2020-06-01T00:00:00Z"), events.into_iter());
+
+        assert_eq!(event_index, 3);
+        assert_eq!(indexes(events), vec!["3"]);
+    }
+
+    #[test]
+    fn thread_without_new_events() {
+        let events = vec![event(1, "2019-01-01T00:00:00Z")];
+
+        let (event_index, events) =
+            new_events(None, Some("2020-06-01T00:00:00Z"), events.into_iter());
+
+        // the old events are recorded so they are not yielded on next poll either.
+        assert_eq!(event_index, 1);
+        assert!(events.is_empty());
+    }
+}